@@ -1,25 +1,279 @@
+use std::{borrow::Cow, cmp::Ordering};
+
 use indexmap::IndexMap;
 use nom::{
-    branch::alt,
-    bytes::complete::{tag, take_until},
     character::complete::{char, digit1},
-    combinator::{map, map_parser, map_res, opt, peek, rest},
-    multi::{fold_many0, length_data, many0},
-    sequence::{delimited, pair, terminated, tuple},
-    Finish, IResult,
+    combinator::{map, map_parser, opt, recognize},
+    error::{Error as NomError, ErrorKind},
+    multi::length_data,
+    sequence::{delimited, pair, terminated},
+    Err as NomErr, IResult, Needed,
 };
 
-use super::{DecodingError, Object, Token, Value};
+use super::{
+    DecodingError, DecodingErrorKind, Limit, Object, ObjectKind, PathSegment, Token, Value, Visitor,
+};
 
 pub struct Decoder<'a> {
+    input: &'a [u8],
     bytes: &'a [u8],
+    mode: DecodingMode,
+    limits: DecoderLimits,
+    warnings: Vec<DecodingError>,
+    /// Offset of the last token read, errors are reported there
+    token_offset: usize,
+    /// The lists and dictionaries being read, from the outermost one
+    frames: Vec<Frame<'a>>,
+}
+/// Reads the items of a list, the offsets of which are kept to know its span.
+///
+/// The offset following the end of the list is only known once it has been read, nothing more is
+/// read from the decoder after that.
+pub struct ListDecoder<'obj, 'de: 'obj>(&'obj mut Decoder<'de>, usize, Option<usize>);
+/// Reads the pairs of a dictionary, the offsets of which are kept as for a [`ListDecoder`], along
+/// with the key [`DictionaryDecoder::get`] has read past.
+pub struct DictionaryDecoder<'obj, 'de: 'obj>(
+    &'obj mut Decoder<'de>,
+    usize,
+    Option<usize>,
+    Option<&'de [u8]>,
+);
+/// Iterates over the tokens of an object and their offsets, see [`Decoder::tokens`].
+pub struct Tokens<'a, 'de> {
+    decoder: &'a mut Decoder<'de>,
+    /// Whether the innermost dictionary expects a key rather than a value
+    expect_key: bool,
+    done: bool,
+}
+
+/// How closely the input has to follow the canonical form of bencode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DecodingMode {
+    /// Accepts integers and lengths with leading zeros, `i-0e` and dictionaries with unsorted or
+    /// duplicate keys.
+    #[default]
+    Permissive,
+    /// Rejects anything but canonical bencode, which is required for the info hash of a torrent
+    /// to be stable.
+    Strict,
+    /// Accepts the same input as [`Permissive`](DecodingMode::Permissive), but records every
+    /// deviation from canonical bencode as a [warning](Decoder::warnings).
+    Lenient,
+}
+
+/// Bounds on the resources a document may use, to decode untrusted input safely.
+///
+/// Exceeding any of them fails with [`LimitExceeded`](DecodingErrorKind::LimitExceeded), all of
+/// them are unlimited by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecoderLimits {
+    /// Maximum number of nested lists and dictionaries.
+    pub max_depth: usize,
+    /// Maximum length of a single byte string, checked before reading it.
+    pub max_byte_string_length: usize,
+    /// Maximum number of items of a list or of pairs of a dictionary.
+    pub max_items: usize,
+    /// Maximum number of bytes read.
+    pub max_total_bytes: usize,
+}
+
+impl DecoderLimits {
+    pub const fn unlimited() -> Self {
+        Self {
+            max_depth: usize::MAX,
+            max_byte_string_length: usize::MAX,
+            max_items: usize::MAX,
+            max_total_bytes: usize::MAX,
+        }
+    }
+}
+
+impl Default for DecoderLimits {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+/// A list or a dictionary being built by [`Decoder::decode_value`], along with the key of the
+/// value being decoded.
+enum PartialValue<'a> {
+    List(Vec<Value<'a>>),
+    Dictionary(IndexMap<Cow<'a, [u8]>, Value<'a>>, Option<&'a [u8]>),
+}
+
+/// Where the decoder is inside a list or a dictionary, used to build the path of errors.
+#[derive(Clone, Copy)]
+enum Frame<'a> {
+    List {
+        index: Option<usize>,
+    },
+    Dictionary {
+        key: Option<&'a [u8]>,
+        length: usize,
+    },
 }
-pub struct ListDecoder<'obj, 'de: 'obj>(&'obj mut Decoder<'de>);
-pub struct DictionaryDecoder<'obj, 'de: 'obj>(&'obj mut Decoder<'de>);
 
 impl<'de> Decoder<'de> {
     pub const fn new(bytes: &'de [u8]) -> Self {
-        Self { bytes }
+        Self {
+            input: bytes,
+            bytes,
+            mode: DecodingMode::Permissive,
+            limits: DecoderLimits::unlimited(),
+            warnings: Vec::new(),
+            token_offset: 0,
+            frames: Vec::new(),
+        }
+    }
+
+    pub const fn with_mode(mut self, mode: DecodingMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub const fn mode(&self) -> DecodingMode {
+        self.mode
+    }
+
+    pub const fn with_limits(mut self, limits: DecoderLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub const fn limits(&self) -> DecoderLimits {
+        self.limits
+    }
+
+    /// Returns the deviations from canonical bencode met so far in lenient mode, as the errors
+    /// strict mode would have failed with.
+    pub fn warnings(&self) -> &[DecodingError] {
+        &self.warnings
+    }
+
+    pub fn take_warnings(&mut self) -> Vec<DecodingError> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Returns an independent decoder at the same position, used to look ahead.
+    ///
+    /// Warnings are only recorded by the original decoder, which reads the same input again.
+    pub(crate) fn fork(&self) -> Decoder<'de> {
+        Decoder {
+            input: self.input,
+            bytes: self.bytes,
+            mode: self.mode,
+            limits: self.limits,
+            warnings: Vec::new(),
+            token_offset: self.token_offset,
+            frames: self.frames.clone(),
+        }
+    }
+
+    /// Returns the last token read as it is encoded in the input.
+    fn encoded_token(&self) -> &'de [u8] {
+        &self.input[self.token_offset..self.offset()]
+    }
+
+    /// Returns the number of bytes read so far.
+    pub const fn offset(&self) -> usize {
+        self.input.len() - self.bytes.len()
+    }
+
+    /// Returns the bytes that have not been read yet.
+    pub const fn remaining(&self) -> &'de [u8] {
+        self.bytes
+    }
+
+    /// Checks that the whole input has been read.
+    pub fn finish(&self) -> Result<(), DecodingError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            let offset = self.offset();
+            Err(DecodingError::trailing_data(offset).located(offset, Vec::new))
+        }
+    }
+
+    /// Locates `error` at the last token read, unless it has already been located.
+    ///
+    /// Decoding stops at the first error, so the position of the decoder is the position of the
+    /// error. This is done by [`FromBencode::from_bencode`](crate::FromBencode::from_bencode)
+    /// and needs to be called when decoding objects by hand.
+    pub fn locate(&self, error: DecodingError) -> DecodingError {
+        error.located(self.token_offset, || self.path())
+    }
+
+    fn path(&self) -> Vec<PathSegment> {
+        self.frames
+            .iter()
+            .filter_map(|frame| match *frame {
+                Frame::List { index } => index.map(PathSegment::Index),
+                Frame::Dictionary { key, .. } => {
+                    key.map(|key| PathSegment::Key(String::from_utf8_lossy(key).into_owned()))
+                }
+            })
+            .collect()
+    }
+
+    /// Locates an error at the current position rather than at the last token read.
+    fn limit_exceeded(&mut self, limit: Limit, maximum: usize) -> DecodingError {
+        self.token_offset = self.offset();
+        self.locate(DecodingError::limit_exceeded(limit, maximum))
+    }
+
+    /// Moves to the next item of the innermost list, unless the list ends here.
+    fn enter_list_item(&mut self) -> Result<(), DecodingError> {
+        if self.bytes.first() == Some(&b'e') {
+            return Ok(());
+        }
+
+        if let Some(Frame::List { index }) = self.frames.last_mut() {
+            let next = index.map_or(0, |index| index + 1);
+            *index = Some(next);
+
+            if next >= self.limits.max_items {
+                return Err(self.limit_exceeded(Limit::Items, self.limits.max_items));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Moves to the pair of `key` in the innermost dictionary, checking that keys are sorted.
+    fn enter_dictionary_key(&mut self, key: &'de [u8]) -> Result<(), DecodingError> {
+        let (previous, length) = match self.frames.last_mut() {
+            Some(Frame::Dictionary {
+                key: current,
+                length,
+            }) => {
+                *length += 1;
+                (current.replace(key), *length)
+            }
+            _ => return Ok(()),
+        };
+
+        if length > self.limits.max_items {
+            return Err(self.limit_exceeded(Limit::Items, self.limits.max_items));
+        }
+
+        match previous {
+            Some(previous) if key == previous => self.deviate(DecodingError::duplicate_key(key)),
+            Some(previous) if key < previous => self.deviate(DecodingError::unsorted_key(key)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Enters a list or a dictionary.
+    fn push_frame(&mut self, frame: Frame<'de>) -> Result<(), DecodingError> {
+        if self.frames.len() >= self.limits.max_depth {
+            return Err(self.locate(DecodingError::limit_exceeded(
+                Limit::Depth,
+                self.limits.max_depth,
+            )));
+        }
+
+        self.frames.push(frame);
+        Ok(())
     }
 
     /// Decodes a byte string without wrapping it into a Token.
@@ -31,169 +285,740 @@ impl<'de> Decoder<'de> {
     }
 
     /// Returns a [byte string](Token::ByteString)
-    pub fn decode_byte_string_token(bytes: &[u8]) -> IResult<&[u8], Token> {
+    pub fn decode_byte_string_token(bytes: &[u8]) -> IResult<&[u8], Token<'_>> {
         map(Self::decode_byte_string_raw, Token::ByteString)(bytes)
     }
 
-    /// Returns a [byte string](Token::ByteString)
-    pub fn decode_byte_string(bytes: &[u8]) -> IResult<&[u8], Value> {
-        map(Self::decode_byte_string_raw, Value::ByteString)(bytes)
+    /// Returns a [byte string](Value::ByteString)
+    pub fn decode_byte_string(bytes: &[u8]) -> IResult<&[u8], Value<'_>> {
+        map(Self::decode_byte_string_raw, |byte_string| {
+            Value::ByteString(Cow::Borrowed(byte_string))
+        })(bytes)
     }
 
-    // TODO: this function def needs some optimization
     fn decode_integer_raw(bytes: &[u8]) -> IResult<&[u8], &[u8]> {
-        map_parser(
-            delimited(char('i'), take_until("e"), char('e')),
-            map_res(
-                tuple((peek(tuple((opt(tag(b"-")), digit1))), rest)),
-                |((sign, _integer), complete)| {
-                    if let Some(sign) = sign {
-                        if sign != b"-" && complete == b"-0" {
-                            Err(DecodingError::Unknown)
-                        } else {
-                            Ok(complete)
-                        }
-                    } else {
-                        Ok(complete)
-                    }
-                },
-            ),
+        delimited(
+            char('i'),
+            recognize(pair(opt(char('-')), digit1)),
+            char('e'),
         )(bytes)
     }
 
     /// Returns an integer leaving it into its byte form.
-    fn decode_integer_token(bytes: &[u8]) -> IResult<&[u8], Token> {
+    fn decode_integer_token(bytes: &[u8]) -> IResult<&[u8], Token<'_>> {
         map(Self::decode_integer_raw, Token::Integer)(bytes)
     }
 
-    /// Returns an integer leaving it into its byte form.
-    fn decode_integer(bytes: &[u8]) -> IResult<&[u8], Value> {
-        map(Self::decode_integer_raw, Value::Integer)(bytes)
-    }
-
     /// Decodes a list directly returning a value instead of a token
-    pub fn decode_list(bytes: &[u8]) -> IResult<&[u8], Value> {
-        map(
-            delimited(char('l'), many0(Self::decode_any), char('e')),
-            Value::List,
-        )(bytes)
+    pub fn decode_list(bytes: &[u8]) -> IResult<&[u8], Value<'_>> {
+        Self::decode_starting_with(b'l', bytes)
     }
 
     /// Decodes a dictionary directly returning a value instead of a token
-    pub fn decode_dictionaries(bytes: &[u8]) -> IResult<&[u8], Value> {
-        map(
-            delimited(
-                char('d'),
-                fold_many0(
-                    pair(Self::decode_byte_string_raw, Self::decode_any),
-                    IndexMap::new,
-                    |mut dict, (key, value)| {
-                        dict.insert(key, value);
-                        dict
-                    },
-                ),
-                char('e'),
-            ),
-            Value::Dictionary,
+    pub fn decode_dictionaries(bytes: &[u8]) -> IResult<&[u8], Value<'_>> {
+        Self::decode_starting_with(b'd', bytes)
+    }
+
+    fn decode_starting_with(first: u8, bytes: &[u8]) -> IResult<&[u8], Value<'_>> {
+        match bytes.first() {
+            Some(&byte) if byte == first => Self::decode_any(bytes),
+            Some(_) => Err(NomErr::Error(NomError::new(bytes, ErrorKind::Char))),
+            None => Err(NomErr::Incomplete(Needed::new(1))),
+        }
+    }
+
+    /// Decodes the value at the start of `bytes` with [`Decoder::decode_value`], as a nom parser.
+    ///
+    /// The end of the input is reported as [`Incomplete`](NomErr::Incomplete), any other error
+    /// points at the token that could not be read.
+    pub fn decode_any(bytes: &[u8]) -> IResult<&[u8], Value<'_>> {
+        let mut decoder = Decoder::new(bytes);
+
+        match decoder.decode_value() {
+            Ok(value) => Ok((decoder.remaining(), value)),
+            Err(error) => match (error.kind(), error.offset()) {
+                (DecodingErrorKind::UnexpectedEof, _) => Err(NomErr::Incomplete(Needed::Unknown)),
+                (_, offset) => {
+                    let input = &bytes[offset.unwrap_or(0)..];
+                    Err(NomErr::Error(NomError::new(input, ErrorKind::Verify)))
+                }
+            },
+        }
+    }
+
+    /// Decodes `bytes`, which must hold a single document and nothing after it.
+    pub fn decode(bytes: &[u8]) -> Result<Value<'_>, DecodingError> {
+        let mut decoder = Decoder::new(bytes);
+        let value = decoder.decode_value()?;
+        decoder.finish()?;
+
+        Ok(value)
+    }
+
+    /// Decodes the document at the start of `bytes`, returning it with the bytes following it.
+    pub fn decode_prefix(bytes: &[u8]) -> Result<(Value<'_>, &[u8]), DecodingError> {
+        let mut decoder = Decoder::new(bytes);
+        let value = decoder.decode_value()?;
+
+        Ok((value, decoder.remaining()))
+    }
+
+    /// Decodes the next object into a [`Value`], following the mode and limits of the decoder.
+    ///
+    /// The lists and dictionaries being built are kept on an explicit stack, so that arbitrarily
+    /// deep documents do not overflow the native one.
+    pub fn decode_value(&mut self) -> Result<Value<'de>, DecodingError> {
+        self.build_value(Vec::new())
+    }
+
+    /// Decodes values into the innermost of `stack` until it is complete.
+    fn build_value(
+        &mut self,
+        mut stack: Vec<PartialValue<'de>>,
+    ) -> Result<Value<'de>, DecodingError> {
+        loop {
+            let token = match stack.last_mut() {
+                None => self.next_nested_token()?,
+                Some(PartialValue::List(_)) => {
+                    self.enter_list_item()?;
+                    self.next_nested_token()?
+                }
+                Some(PartialValue::Dictionary(_, Some(_))) => self.next_nested_token()?,
+                Some(PartialValue::Dictionary(_, key)) => match self.next_key()? {
+                    Some(next_key) => {
+                        *key = Some(next_key);
+                        self.next_nested_token()?
+                    }
+                    None => Token::End,
+                },
+            };
+
+            let value = match token {
+                Token::ByteString(byte_string) => Value::ByteString(Cow::Borrowed(byte_string)),
+                Token::Integer(integer) => {
+                    let integer = Object::Integer(integer, None).decode();
+                    Value::Integer(integer.map_err(|error| self.locate(error))?)
+                }
+                Token::ListStart => {
+                    stack.push(PartialValue::List(Vec::new()));
+                    continue;
+                }
+                Token::DictionaryStart => {
+                    stack.push(PartialValue::Dictionary(IndexMap::new(), None));
+                    continue;
+                }
+                Token::End => match stack.pop() {
+                    Some(PartialValue::List(values)) => Value::List(values.into()),
+                    Some(PartialValue::Dictionary(values, _)) => Value::Dictionary(values.into()),
+                    None => return Err(self.locate(DecodingError::unexpected_byte(b'e'))),
+                },
+            };
+
+            match stack.last_mut() {
+                None => return Ok(value),
+                Some(PartialValue::List(values)) => values.push(value),
+                Some(PartialValue::Dictionary(values, key)) => {
+                    if let Some(key) = key.take() {
+                        values.insert(Cow::Borrowed(key), value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reads the token at the start of `bytes`.
+    fn token(bytes: &[u8]) -> Result<(&[u8], Token<'_>), DecodingError> {
+        match bytes.first() {
+            None => Err(DecodingErrorKind::UnexpectedEof.into()),
+            Some(b'0'..=b'9') => Self::decode_byte_string_token(bytes)
+                .map_err(|error| Self::map_error(error, DecodingErrorKind::InvalidLengthPrefix)),
+            Some(b'i') => Self::decode_integer_token(bytes)
+                .map_err(|error| Self::map_error(error, DecodingErrorKind::InvalidInteger)),
+            Some(b'l') => Ok((&bytes[1..], Token::ListStart)),
+            Some(b'd') => Ok((&bytes[1..], Token::DictionaryStart)),
+            Some(b'e') => Ok((&bytes[1..], Token::End)),
+            Some(&found) => Err(DecodingError::unexpected_byte(found)),
+        }
+    }
+
+    /// Reports a parser running out of input as the end of the document and any other failure
+    /// as `error`.
+    fn map_error(error: NomErr<NomError<&[u8]>>, kind: DecodingErrorKind) -> DecodingError {
+        match error {
+            NomErr::Error(error) | NomErr::Failure(error) if !error.input.is_empty() => kind.into(),
+            _ => DecodingErrorKind::UnexpectedEof.into(),
+        }
+    }
+
+    /// Reads the length prefix of the byte string at the start of `bytes`, if there is one.
+    fn length_prefix(bytes: &[u8]) -> Option<u64> {
+        map_parser(
+            terminated(digit1, char(':')),
+            nom::character::complete::u64::<_, NomError<_>>,
         )(bytes)
+        .ok()
+        .map(|(_, length)| length)
     }
 
-    pub fn decode_any(bytes: &[u8]) -> IResult<&[u8], Value> {
-        alt((
-            Self::decode_byte_string,
-            Self::decode_integer,
-            Self::decode_list,
-            Self::decode_dictionaries,
-        ))(bytes)
+    fn next_token(&mut self) -> Result<Token<'de>, DecodingError> {
+        self.token_offset = self.offset();
+
+        let max_length = self.limits.max_byte_string_length;
+        if max_length != usize::MAX {
+            if let Some(length) = Self::length_prefix(self.bytes) {
+                if length > max_length as u64 {
+                    let error = DecodingError::limit_exceeded(Limit::ByteStringLength, max_length);
+                    return Err(self.locate(error));
+                }
+            }
+        }
+
+        let (bytes, token) = Self::token(self.bytes).map_err(|error| self.locate(error))?;
+
+        if self.input.len() - bytes.len() > self.limits.max_total_bytes {
+            let error =
+                DecodingError::limit_exceeded(Limit::TotalBytes, self.limits.max_total_bytes);
+            return Err(self.locate(error));
+        }
+
+        if self.mode != DecodingMode::Permissive {
+            if let Err(deviation) = self.check_canonical(&token) {
+                self.deviate(deviation)?;
+            }
+        }
+
+        self.bytes = bytes;
+
+        Ok(token)
     }
 
-    pub fn decode(bytes: &[u8]) -> Result<Value, DecodingError> {
-        Self::decode_any(bytes)
-            .finish()
-            .map(|(_rest, value)| value)
-            .map_err(|_error| DecodingError::Unknown)
-    }
-
-    fn next_token(&mut self) -> Result<Option<Token<'de>>, DecodingError> {
-        alt((
-            Self::decode_byte_string_token,
-            Self::decode_integer_token,
-            map(char('l'), |_| Token::ListStart),
-            map(char('d'), |_| Token::DictionaryStart),
-            map(char('e'), |_| Token::End),
-        ))(self.bytes)
-        .finish()
-        .map(|(bytes, token)| {
-            self.bytes = bytes;
-            Some(token)
-        })
-        .map_err(|_error| DecodingError::Unknown) // TODO: Map to an actual error
+    /// Reads the next token, entering or leaving lists and dictionaries.
+    fn next_nested_token(&mut self) -> Result<Token<'de>, DecodingError> {
+        let token = self.next_token()?;
+
+        match token {
+            Token::End => {
+                self.frames.pop();
+            }
+            Token::ListStart => self.push_frame(Frame::List { index: None })?,
+            Token::DictionaryStart => self.push_frame(Frame::Dictionary {
+                key: None,
+                length: 0,
+            })?,
+            Token::ByteString(_) | Token::Integer(_) => {}
+        }
+
+        Ok(token)
+    }
+
+    /// Reads the next key of the innermost dictionary, or returns `None` at its end.
+    fn next_key(&mut self) -> Result<Option<&'de [u8]>, DecodingError> {
+        match self.next_token()? {
+            Token::End => {
+                self.frames.pop();
+                Ok(None)
+            }
+            Token::ByteString(key) => {
+                self.enter_dictionary_key(key)?;
+
+                // Checked ahead, reading the end would leave the dictionary and lose the key
+                if self.bytes.first() == Some(&b'e') {
+                    let error = DecodingErrorKind::MissingDictionaryValue.into();
+                    return Err(self.locate(error));
+                }
+
+                Ok(Some(key))
+            }
+            token => {
+                let error = DecodingError::unexpected_object("ByteString", token.name());
+                Err(self.locate(error))
+            }
+        }
+    }
+
+    /// Reads the rest of the innermost list or dictionary, the nested ones included.
+    ///
+    /// Nested values are read one token at a time instead of recursively, with the same checks as
+    /// [`Decoder::decode_value`], so that deeply nested input can not overflow the stack.
+    /// `value_pending` tells that the key of the innermost dictionary has been read already.
+    fn skip_frame(&mut self, value_pending: bool) -> Result<(), DecodingError> {
+        let depth = self.frames.len();
+        // Only false right after a key, when its value comes next
+        let mut expect_key = !value_pending;
+
+        while self.frames.len() >= depth {
+            match self.frames.last() {
+                Some(Frame::Dictionary { .. }) if expect_key => {
+                    expect_key = self.next_key()?.is_none();
+                }
+                Some(Frame::List { .. }) => {
+                    self.enter_list_item()?;
+                    self.next_nested_token()?;
+                }
+                _ => {
+                    self.next_nested_token()?;
+                    expect_key = true;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fails on a deviation from canonical bencode in strict mode, records it otherwise.
+    fn deviate(&mut self, deviation: DecodingError) -> Result<(), DecodingError> {
+        let deviation = self.locate(deviation);
+
+        match self.mode {
+            DecodingMode::Strict => Err(deviation),
+            DecodingMode::Lenient => {
+                self.warnings.push(deviation);
+                Ok(())
+            }
+            DecodingMode::Permissive => Ok(()),
+        }
+    }
+
+    /// Checks that `token`, which has not been consumed yet, is in its canonical form.
+    fn check_canonical(&self, token: &Token<'de>) -> Result<(), DecodingError> {
+        match token {
+            Token::ByteString(_) if self.bytes.starts_with(b"0") && self.bytes[1] != b':' => {
+                Err(DecodingErrorKind::LeadingZeroInLength.into())
+            }
+            Token::Integer(b"-0") => Err(DecodingErrorKind::NegativeZero.into()),
+            Token::Integer([b'0', _, ..] | [b'-', b'0', ..]) => {
+                Err(DecodingErrorKind::LeadingZero.into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns an iterator over the tokens of the next object, each with its offset.
+    ///
+    /// Unlike with [`Decoder::next_object`], the end of a list or a dictionary is yielded as
+    /// [`Token::End`], while the iterator itself ends once the object is complete, or right away
+    /// at the end of the input. Nesting, the mode and the limits are checked along the way.
+    pub fn tokens(&mut self) -> Tokens<'_, 'de> {
+        let done = self.bytes.is_empty() && self.frames.is_empty();
+
+        Tokens {
+            decoder: self,
+            expect_key: false,
+            done,
+        }
+    }
+
+    /// Reads the next object, calling `visitor` for each of its parts.
+    ///
+    /// As with [`Decoder::next_object`], [`Decoder::finish`] checks that nothing follows it.
+    pub fn visit<V: Visitor<'de>>(&mut self, visitor: &mut V) -> Result<(), DecodingError> {
+        let mut tokens = self.tokens();
+        if tokens.done {
+            let error = DecodingErrorKind::UnexpectedEof.into();
+            return Err(tokens.decoder.locate(error));
+        }
+
+        while !tokens.done {
+            let is_key = tokens.expect_key;
+            let token = match tokens.next() {
+                Some(token) => token?.1,
+                None => break,
+            };
+
+            let result = match token {
+                Token::ByteString(key) if is_key => visitor.visit_key(key),
+                Token::ByteString(byte_string) => visitor.visit_byte_string(byte_string),
+                Token::Integer(integer) => visitor.visit_integer(integer),
+                Token::ListStart => visitor.begin_list(),
+                Token::DictionaryStart => visitor.begin_dictionary(),
+                Token::End => visitor.end(),
+            };
+            result.map_err(|error| tokens.decoder.locate(error))?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the kind of the next object, or [`ObjectKind::End`] at the end of a list or a
+    /// dictionary, without reading it.
+    ///
+    /// Only the first byte is looked at, so a malformed object is reported when it is read.
+    pub fn peek_kind(&self) -> Result<ObjectKind, DecodingError> {
+        match self.bytes.first() {
+            Some(b'0'..=b'9') => Ok(ObjectKind::ByteString),
+            Some(b'i') => Ok(ObjectKind::Integer),
+            Some(b'l') => Ok(ObjectKind::List),
+            Some(b'd') => Ok(ObjectKind::Dictionary),
+            Some(b'e') => Ok(ObjectKind::End),
+            Some(&found) => {
+                Err(DecodingError::unexpected_byte(found).located(self.offset(), || self.path()))
+            }
+            None => {
+                let error = DecodingError::from(DecodingErrorKind::UnexpectedEof);
+                Err(error.located(self.offset(), || self.path()))
+            }
+        }
     }
 
     pub fn next_object<'obj>(&'obj mut self) -> Result<Option<Object<'obj, 'de>>, DecodingError> {
-        Ok(match self.next_token()? {
-            None | Some(Token::End) => None,
-            Some(Token::ByteString(byte_string)) => Some(Object::ByteString(byte_string)),
-            Some(Token::Integer(integer)) => Some(Object::Integer(integer)),
-            Some(Token::ListStart) => Some(Object::List(ListDecoder::new(self))),
-            Some(Token::DictionaryStart) => Some(Object::Dictionary(DictionaryDecoder::new(self))),
+        Ok(match self.next_nested_token()? {
+            Token::End => None,
+            Token::ByteString(byte_string) => {
+                Some(Object::ByteString(byte_string, Some(self.encoded_token())))
+            }
+            Token::Integer(integer) => Some(Object::Integer(integer, Some(self.encoded_token()))),
+            Token::ListStart => Some(Object::List(ListDecoder::new(self))),
+            Token::DictionaryStart => Some(Object::Dictionary(DictionaryDecoder::new(self))),
         })
     }
 }
 
+impl<'de> Tokens<'_, 'de> {
+    fn next_token(&mut self) -> Result<Token<'de>, DecodingError> {
+        let decoder = &mut *self.decoder;
+
+        if self.expect_key {
+            return Ok(match decoder.next_key()? {
+                Some(key) => {
+                    self.expect_key = false;
+                    Token::ByteString(key)
+                }
+                None => {
+                    self.expect_key =
+                        matches!(decoder.frames.last(), Some(Frame::Dictionary { .. }));
+                    Token::End
+                }
+            });
+        }
+
+        let in_list = matches!(decoder.frames.last(), Some(Frame::List { .. }));
+        if in_list {
+            decoder.enter_list_item()?;
+        }
+
+        let token = decoder.next_nested_token()?;
+        if token == Token::End && !in_list {
+            return Err(decoder.locate(DecodingError::unexpected_byte(b'e')));
+        }
+
+        self.expect_key = match token {
+            Token::DictionaryStart => true,
+            Token::ListStart => false,
+            _ => matches!(decoder.frames.last(), Some(Frame::Dictionary { .. })),
+        };
+
+        Ok(token)
+    }
+}
+
+impl<'de> Iterator for Tokens<'_, 'de> {
+    type Item = Result<(usize, Token<'de>), DecodingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let token = self.next_token();
+        self.done = token.is_err() || self.decoder.frames.is_empty();
+
+        Some(token.map(|token| (self.decoder.token_offset, token)))
+    }
+}
+
 impl<'obj, 'de: 'obj> ListDecoder<'obj, 'de> {
-    pub const fn as_bytes(self) -> &'de [u8] {
-        self.0.bytes
+    /// Returns the encoded list, from its `l` to its `e`, skipping the items not read yet.
+    pub fn as_bytes(mut self) -> Result<&'de [u8], DecodingError> {
+        let end = self.end()?;
+        Ok(&self.0.input[self.1..end])
+    }
+
+    /// Returns an independent decoder at the same position along with the offsets of the list.
+    pub(crate) fn fork(&self) -> (Decoder<'de>, usize, Option<usize>) {
+        (self.0.fork(), self.1, self.2)
     }
 
+    /// Reads the list which has just been entered by `decoder`.
     pub fn new(decoder: &'obj mut Decoder<'de>) -> Self {
-        Self(decoder)
+        let start = decoder.token_offset;
+        Self(decoder, start, None)
+    }
+
+    pub(crate) fn new_at(
+        decoder: &'obj mut Decoder<'de>,
+        start: usize,
+        end: Option<usize>,
+    ) -> Self {
+        Self(decoder, start, end)
     }
 
     pub fn next_object<'item>(
         &'item mut self,
     ) -> Result<Option<Object<'item, 'de>>, DecodingError> {
-        self.0.next_object()
+        if self.2.is_some() {
+            return Ok(None);
+        }
+
+        self.0.enter_list_item()?;
+        // Only an `e` ends the list
+        let end = self.0.offset() + 1;
+
+        match self.0.next_object()? {
+            None => {
+                self.2 = Some(end);
+                Ok(None)
+            }
+            object => Ok(object),
+        }
+    }
+
+    /// Decodes the remaining items of the list.
+    pub(crate) fn decode_value(self) -> Result<Value<'de>, DecodingError> {
+        if self.2.is_some() {
+            return Ok(Value::List(Vec::new().into()));
+        }
+
+        self.0.build_value(vec![PartialValue::List(Vec::new())])
+    }
+
+    /// Returns the kind of the next item, or [`ObjectKind::End`] at the end of the list, without
+    /// reading it.
+    pub fn peek_kind(&self) -> Result<ObjectKind, DecodingError> {
+        if self.2.is_some() {
+            return Ok(ObjectKind::End);
+        }
+
+        self.0.peek_kind()
+    }
+
+    /// Calls `f` on each of the remaining items, which it has to read or skip.
+    pub fn for_each<F>(&mut self, mut f: F) -> Result<(), DecodingError>
+    where
+        F: FnMut(Object<'_, 'de>) -> Result<(), DecodingError>,
+    {
+        self.try_fold((), |(), object| f(object))
+    }
+
+    /// Folds the remaining items into `init` with `f`, which has to read or skip them, stopping
+    /// at the first error.
+    pub fn try_fold<B, F>(&mut self, init: B, mut f: F) -> Result<B, DecodingError>
+    where
+        F: FnMut(B, Object<'_, 'de>) -> Result<B, DecodingError>,
+    {
+        let mut accumulator = init;
+        while let Some(object) = self.next_object()? {
+            accumulator = f(accumulator, object)?;
+        }
+
+        Ok(accumulator)
     }
 
     pub fn skip(&mut self) -> Result<(), DecodingError> {
-        loop {
-            if self.next_object()?.is_none() {
-                break Ok(());
-            }
+        self.end().map(|_| ())
+    }
+
+    /// Reads the rest of the list, returning the offset following its end.
+    fn end(&mut self) -> Result<usize, DecodingError> {
+        if let Some(end) = self.2 {
+            return Ok(end);
         }
+
+        self.0.skip_frame(false)?;
+        let end = self.0.offset();
+        self.2 = Some(end);
+
+        Ok(end)
     }
 }
 
 impl<'obj, 'de: 'obj> DictionaryDecoder<'obj, 'de> {
-    pub const fn as_bytes(self) -> &'de [u8] {
-        self.0.bytes
+    /// Returns the encoded dictionary, from its `d` to its `e`, skipping the pairs not read yet.
+    pub fn as_bytes(mut self) -> Result<&'de [u8], DecodingError> {
+        let end = self.end()?;
+        Ok(&self.0.input[self.1..end])
+    }
+
+    /// Returns an independent decoder at the same position along with the offsets of the
+    /// dictionary and the key read past.
+    pub(crate) fn fork(&self) -> (Decoder<'de>, usize, Option<usize>, Option<&'de [u8]>) {
+        (self.0.fork(), self.1, self.2, self.3)
     }
 
+    /// Reads the dictionary which has just been entered by `decoder`.
     pub fn new(decoder: &'obj mut Decoder<'de>) -> Self {
-        Self(decoder)
+        let start = decoder.token_offset;
+        Self(decoder, start, None, None)
+    }
+
+    pub(crate) fn new_at(
+        decoder: &'obj mut Decoder<'de>,
+        start: usize,
+        end: Option<usize>,
+        pending_key: Option<&'de [u8]>,
+    ) -> Self {
+        Self(decoder, start, end, pending_key)
+    }
+
+    /// Decodes the remaining pairs of the dictionary.
+    pub(crate) fn decode_value(self) -> Result<Value<'de>, DecodingError> {
+        if self.2.is_some() {
+            return Ok(Value::Dictionary(IndexMap::new().into()));
+        }
+
+        let partial_value = PartialValue::Dictionary(IndexMap::new(), self.3);
+        self.0.build_value(vec![partial_value])
+    }
+
+    fn skip_value(&mut self) -> Result<(), DecodingError> {
+        match self.0.next_object()? {
+            Some(value) => value.skip(),
+            None => Ok(()),
+        }
     }
 
     pub fn next_pair<'item>(
         &'item mut self,
     ) -> Result<Option<(&'de [u8], Object<'item, 'de>)>, DecodingError> {
-        if let Some(Object::ByteString(key)) = self.0.next_object()? {
-            if let Some(value) = self.0.next_object()? {
-                Ok(Some((key, value)))
-            } else {
-                Err(DecodingError::MissingDictionaryValue)
+        match self.next_key()? {
+            Some(key) => Ok(self.0.next_object()?.map(|value| (key, value))),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the value of `key`, skipping the pairs before it.
+    ///
+    /// The keys of a canonical dictionary are sorted, so the search stops at the first greater
+    /// key, which is left to be read next. Several keys can thus be looked up in increasing order
+    /// in a single pass. Use [`DictionaryDecoder::find`] when the keys may be unsorted.
+    pub fn get<'item>(
+        &'item mut self,
+        key: &[u8],
+    ) -> Result<Option<Object<'item, 'de>>, DecodingError> {
+        while let Some(next_key) = self.next_key()? {
+            match next_key.cmp(key) {
+                Ordering::Less => self.skip_value()?,
+                Ordering::Equal => return self.0.next_object(),
+                Ordering::Greater => {
+                    self.3 = Some(next_key);
+                    break;
+                }
             }
-        } else {
-            Ok(None)
         }
+
+        Ok(None)
     }
 
-    pub fn skip(&mut self) -> Result<(), DecodingError> {
-        loop {
-            if self.next_pair()?.is_none() {
-                break Ok(());
+    /// Returns the value of `key`, skipping the pairs before it whatever their order.
+    ///
+    /// When `key` is missing, the whole dictionary is read.
+    pub fn find<'item>(
+        &'item mut self,
+        key: &[u8],
+    ) -> Result<Option<Object<'item, 'de>>, DecodingError> {
+        while let Some(next_key) = self.next_key()? {
+            if next_key == key {
+                return self.0.next_object();
             }
+
+            self.skip_value()?;
         }
+
+        Ok(None)
+    }
+
+    /// Calls `f` with the position in `keys` and the value of each of `keys` found in the
+    /// dictionary, in a single pass.
+    ///
+    /// `f` has to read or skip the values it is given. As with [`DictionaryDecoder::get`], the
+    /// search stops at the first key greater than all of `keys`, which is left to be read next.
+    pub fn get_many<F>(&mut self, keys: &[&[u8]], mut f: F) -> Result<(), DecodingError>
+    where
+        F: FnMut(usize, Object<'_, 'de>) -> Result<(), DecodingError>,
+    {
+        let last_key = match keys.iter().max() {
+            Some(last_key) => *last_key,
+            None => return Ok(()),
+        };
+
+        while let Some(next_key) = self.next_key()? {
+            if next_key > last_key {
+                self.3 = Some(next_key);
+                break;
+            }
+
+            match keys.iter().position(|key| *key == next_key) {
+                Some(index) => {
+                    if let Some(value) = self.0.next_object()? {
+                        f(index, value)?;
+                    }
+                }
+                None => self.skip_value()?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Calls `f` on each of the remaining pairs, the values of which it has to read or skip.
+    pub fn for_each<F>(&mut self, mut f: F) -> Result<(), DecodingError>
+    where
+        F: FnMut(&'de [u8], Object<'_, 'de>) -> Result<(), DecodingError>,
+    {
+        self.try_fold((), |(), key, value| f(key, value))
+    }
+
+    /// Folds the remaining pairs into `init` with `f`, which has to read or skip the values,
+    /// stopping at the first error.
+    pub fn try_fold<B, F>(&mut self, init: B, mut f: F) -> Result<B, DecodingError>
+    where
+        F: FnMut(B, &'de [u8], Object<'_, 'de>) -> Result<B, DecodingError>,
+    {
+        let mut accumulator = init;
+        while let Some((key, value)) = self.next_pair()? {
+            accumulator = f(accumulator, key, value)?;
+        }
+
+        Ok(accumulator)
+    }
+
+    pub fn skip(&mut self) -> Result<(), DecodingError> {
+        self.end().map(|_| ())
+    }
+
+    /// Reads the next key, starting with the one read past, or returns `None` once the end of the
+    /// dictionary has been read.
+    fn next_key(&mut self) -> Result<Option<&'de [u8]>, DecodingError> {
+        if let Some(key) = self.3.take() {
+            return Ok(Some(key));
+        }
+
+        if self.2.is_some() {
+            return Ok(None);
+        }
+
+        // Only an `e` ends the dictionary
+        let end = self.0.offset() + 1;
+        let key = self.0.next_key()?;
+        if key.is_none() {
+            self.2 = Some(end);
+        }
+
+        Ok(key)
+    }
+
+    /// Reads the rest of the dictionary, returning the offset following its end.
+    fn end(&mut self) -> Result<usize, DecodingError> {
+        if let Some(end) = self.2 {
+            return Ok(end);
+        }
+
+        // The value of a key read past comes first
+        let value_pending = self.3.take().is_some();
+        self.0.skip_frame(value_pending)?;
+        let end = self.0.offset();
+        self.2 = Some(end);
+
+        Ok(end)
     }
 }
 
@@ -202,7 +1027,6 @@ mod tests {
     use std::num::NonZeroUsize;
 
     use super::*;
-    use nom::{Err as NomErr, Needed};
 
     #[test]
     fn decode_integer() {
@@ -253,4 +1077,575 @@ mod tests {
             Decoder::decode_byte_string_raw(b"6:hello")
         );
     }
+
+    #[test]
+    fn decode_nom_parsers() {
+        let (rest, value) = Decoder::decode_any(b"li1eei2e").unwrap();
+        assert_eq!(b"i2e", rest);
+        assert!(matches!(&value, Value::List(values) if values.len() == 1));
+
+        assert!(Decoder::decode_dictionaries(b"d1:ai1ee").is_ok());
+        assert!(matches!(
+            Decoder::decode_list(b"d1:ai1ee"),
+            Err(NomErr::Error(NomError {
+                code: ErrorKind::Char,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            Decoder::decode_any(b"li1e"),
+            Err(NomErr::Incomplete(_))
+        ));
+        assert!(matches!(
+            Decoder::decode_any(b"li1exe"),
+            Err(NomErr::Error(NomError { input: b"xe", .. }))
+        ));
+    }
+
+    #[test]
+    fn skip_nested() {
+        let mut decoder = Decoder::new(b"ld1:ali1eee1:bei2e");
+        let mut list = decoder.next_object().unwrap().unwrap().list().unwrap();
+        list.skip().unwrap();
+
+        assert!(matches!(
+            decoder.next_object(),
+            Ok(Some(Object::Integer(b"2", _)))
+        ));
+    }
+
+    #[test]
+    fn as_bytes_after_end() {
+        let mut decoder = Decoder::new(b"lli1eei2ee");
+        let mut outer = decoder.next_object().unwrap().unwrap().list().unwrap();
+        let mut inner = outer.next_object().unwrap().unwrap().list().unwrap();
+        while inner.next_object().unwrap().is_some() {}
+
+        assert!(inner.next_object().unwrap().is_none());
+        inner.skip().unwrap();
+        assert_eq!(b"li1ee", inner.as_bytes().unwrap());
+        assert!(matches!(
+            outer.next_object(),
+            Ok(Some(Object::Integer(b"2", _)))
+        ));
+
+        let mut decoder = Decoder::new(b"ld1:ai1eei2ee");
+        let mut outer = decoder.next_object().unwrap().unwrap().list().unwrap();
+        let mut inner = outer.next_object().unwrap().unwrap().dictionary().unwrap();
+        while inner.next_pair().unwrap().is_some() {}
+
+        assert_eq!(b"d1:ai1ee", inner.as_bytes().unwrap());
+        assert!(matches!(
+            outer.next_object(),
+            Ok(Some(Object::Integer(b"2", _)))
+        ));
+    }
+
+    #[test]
+    fn decode_truncated() {
+        assert!(matches!(
+            Decoder::decode(b"d3:key5:va").map_err(DecodingError::into_kind),
+            Err(DecodingErrorKind::UnexpectedEof)
+        ));
+        assert!(matches!(
+            Decoder::decode(b"li1e").map_err(DecodingError::into_kind),
+            Err(DecodingErrorKind::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn decode_malformed_tokens() {
+        assert!(matches!(
+            Decoder::decode(b"i12ae").map_err(DecodingError::into_kind),
+            Err(DecodingErrorKind::InvalidInteger)
+        ));
+        assert!(matches!(
+            Decoder::decode(b"5-hello").map_err(DecodingError::into_kind),
+            Err(DecodingErrorKind::InvalidLengthPrefix)
+        ));
+        assert!(matches!(
+            Decoder::decode(b"lxe").map_err(DecodingError::into_kind),
+            Err(DecodingErrorKind::UnexpectedByte { found: b'x' })
+        ));
+    }
+
+    #[test]
+    fn next_pair_non_byte_string_key() {
+        let mut decoder = Decoder::new(b"di1ei2ee");
+        let mut dictionary = decoder
+            .next_object()
+            .unwrap()
+            .unwrap()
+            .dictionary()
+            .unwrap();
+
+        assert!(matches!(
+            dictionary.next_pair().map_err(DecodingError::into_kind),
+            Err(DecodingErrorKind::UnexpectedObject {
+                expected_object: "ByteString",
+                actual_object: "Integer"
+            })
+        ));
+    }
+
+    #[test]
+    fn peek_and_fold() {
+        let mut decoder = Decoder::new(b"li1ei2eli3eee");
+        let mut list = decoder.next_object().unwrap().unwrap().try_list().unwrap();
+
+        assert_eq!(ObjectKind::Integer, list.peek_kind().unwrap());
+
+        let sum = list
+            .try_fold(0, |sum, object| match object {
+                Object::Integer(integer, _) => Ok(sum + integer[0] - b'0'),
+                object => object.skip().map(|()| sum),
+            })
+            .unwrap();
+
+        assert_eq!(3, sum);
+        assert!(matches!(
+            decoder.peek_kind().map_err(DecodingError::into_kind),
+            Err(DecodingErrorKind::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn dictionary_for_each() {
+        let mut decoder = Decoder::new(b"d1:ai1e1:bli2eee");
+        let mut dictionary = decoder
+            .next_object()
+            .unwrap()
+            .unwrap()
+            .try_dictionary()
+            .unwrap();
+        let mut keys = Vec::new();
+
+        dictionary
+            .for_each(|key, value| {
+                keys.push(key);
+                value.skip()
+            })
+            .unwrap();
+
+        assert_eq!(vec![b"a".as_ref(), b"b".as_ref()], keys);
+    }
+
+    #[test]
+    fn tokens() {
+        let mut decoder = Decoder::new(b"d1:ali1ee1:bdee");
+        let tokens = decoder.tokens().collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(
+            vec![
+                (0, Token::DictionaryStart),
+                (1, Token::ByteString(b"a")),
+                (4, Token::ListStart),
+                (5, Token::Integer(b"1")),
+                (8, Token::End),
+                (9, Token::ByteString(b"b")),
+                (12, Token::DictionaryStart),
+                (13, Token::End),
+                (14, Token::End),
+            ],
+            tokens
+        );
+        assert!(decoder.tokens().next().is_none());
+    }
+
+    #[test]
+    fn tokens_unbalanced() {
+        let mut decoder = Decoder::new(b"li1e");
+        let mut tokens = decoder.tokens();
+
+        assert_eq!(
+            Some((1, Token::Integer(b"1"))),
+            tokens.nth(1).transpose().unwrap()
+        );
+        assert!(matches!(
+            tokens.next().transpose().map_err(DecodingError::into_kind),
+            Err(DecodingErrorKind::UnexpectedEof)
+        ));
+        assert!(tokens.next().is_none());
+
+        assert!(matches!(
+            Decoder::new(b"e")
+                .tokens()
+                .next()
+                .transpose()
+                .map_err(DecodingError::into_kind),
+            Err(DecodingErrorKind::UnexpectedByte { found: b'e' })
+        ));
+        assert!(matches!(
+            Decoder::new(b"di1ei2ee")
+                .tokens()
+                .nth(1)
+                .transpose()
+                .map_err(DecodingError::into_kind),
+            Err(DecodingErrorKind::UnexpectedObject { .. })
+        ));
+    }
+
+    #[test]
+    fn visit() {
+        #[derive(Default)]
+        struct Outline(String);
+
+        impl<'de> Visitor<'de> for Outline {
+            fn visit_byte_string(&mut self, _byte_string: &'de [u8]) -> Result<(), DecodingError> {
+                self.0.push('s');
+                Ok(())
+            }
+
+            fn visit_integer(&mut self, integer: &'de [u8]) -> Result<(), DecodingError> {
+                if integer == b"0" {
+                    return Err(DecodingErrorKind::InvalidInteger.into());
+                }
+
+                self.0.push('i');
+                Ok(())
+            }
+
+            fn begin_list(&mut self) -> Result<(), DecodingError> {
+                self.0.push('[');
+                Ok(())
+            }
+
+            fn begin_dictionary(&mut self) -> Result<(), DecodingError> {
+                self.0.push('{');
+                Ok(())
+            }
+
+            fn visit_key(&mut self, key: &'de [u8]) -> Result<(), DecodingError> {
+                self.0.push_str(std::str::from_utf8(key).unwrap());
+                self.0.push(':');
+                Ok(())
+            }
+
+            fn end(&mut self) -> Result<(), DecodingError> {
+                self.0.push(']');
+                Ok(())
+            }
+        }
+
+        let mut outline = Outline::default();
+        Decoder::new(b"d1:ali1e1:be1:bdee")
+            .visit(&mut outline)
+            .unwrap();
+
+        assert_eq!("{a:[is]b:{]]", outline.0);
+
+        let error = Decoder::new(b"d1:ali1ei0eee")
+            .visit(&mut Outline::default())
+            .unwrap_err();
+
+        assert_eq!(Some(8), error.offset());
+        assert_eq!(
+            [PathSegment::Key("a".to_string()), PathSegment::Index(1)],
+            error.path()
+        );
+    }
+
+    #[test]
+    fn dictionary_get() {
+        let mut decoder = Decoder::new(b"d1:ai1e1:ci3e1:ei5ee");
+        let mut dictionary = decoder
+            .next_object()
+            .unwrap()
+            .unwrap()
+            .try_dictionary()
+            .unwrap();
+
+        assert!(dictionary.get(b"b").unwrap().is_none());
+        assert_eq!(
+            Some(b"3".as_ref()),
+            dictionary.get(b"c").unwrap().and_then(Object::integer)
+        );
+        assert_eq!(
+            Some(b"5".as_ref()),
+            dictionary.get(b"e").unwrap().and_then(Object::integer)
+        );
+        assert!(dictionary.next_pair().unwrap().is_none());
+    }
+
+    #[test]
+    fn dictionary_value_after_get() {
+        let mut decoder = Decoder::new(b"d1:ai1e1:ci3e1:ei5ee");
+        let mut dictionary = decoder
+            .next_object()
+            .unwrap()
+            .unwrap()
+            .try_dictionary()
+            .unwrap();
+
+        assert!(dictionary.get(b"b").unwrap().is_none());
+
+        let value = dictionary.decode_value().unwrap();
+        assert!(matches!(
+            value,
+            Value::Dictionary(pairs) if pairs.keys().map(AsRef::as_ref).eq([b"c".as_ref(), b"e".as_ref()])
+        ));
+    }
+
+    #[test]
+    fn dictionary_get_after_end() {
+        let mut decoder = Decoder::new(b"ld1:ai1eei7ee");
+        let mut list = decoder.next_object().unwrap().unwrap().try_list().unwrap();
+        let mut dictionary = list
+            .next_object()
+            .unwrap()
+            .unwrap()
+            .try_dictionary()
+            .unwrap();
+
+        assert!(dictionary.get(b"b").unwrap().is_none());
+        assert!(dictionary.get(b"c").unwrap().is_none());
+        assert!(dictionary.find(b"d").unwrap().is_none());
+        assert!(dictionary.next_pair().unwrap().is_none());
+        assert!(matches!(
+            list.next_object(),
+            Ok(Some(Object::Integer(b"7", _)))
+        ));
+    }
+
+    #[test]
+    fn dictionary_find_unsorted() {
+        let mut decoder = Decoder::new(b"d1:ci3e1:ai1ee");
+        let mut dictionary = decoder
+            .next_object()
+            .unwrap()
+            .unwrap()
+            .try_dictionary()
+            .unwrap();
+
+        assert!(dictionary.get(b"a").unwrap().is_none());
+        assert_eq!(
+            Some(b"1".as_ref()),
+            dictionary.find(b"a").unwrap().and_then(Object::integer)
+        );
+    }
+
+    #[test]
+    fn dictionary_get_many() {
+        let mut decoder = Decoder::new(b"d1:ai1e1:bli2ee1:ci3e1:di4ee");
+        let mut dictionary = decoder
+            .next_object()
+            .unwrap()
+            .unwrap()
+            .try_dictionary()
+            .unwrap();
+        let mut found = Vec::new();
+
+        dictionary
+            .get_many(&[b"c", b"a"], |index, value| {
+                found.push((index, value.try_integer()?));
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(vec![(1, b"1".as_ref()), (0, b"3".as_ref())], found);
+        assert_eq!(b"d", dictionary.next_pair().unwrap().unwrap().0);
+    }
+
+    #[test]
+    fn error_offset() {
+        assert_eq!(Some(4), Decoder::decode(b"li1exe").unwrap_err().offset());
+
+        let mut decoder = Decoder::new(b"d1:ai1e1:bxe");
+        let mut dictionary = decoder
+            .next_object()
+            .unwrap()
+            .unwrap()
+            .dictionary()
+            .unwrap();
+        dictionary.next_pair().unwrap();
+        let error = dictionary.next_pair().map(|_| ()).unwrap_err();
+
+        assert_eq!(Some(10), error.offset());
+        assert_eq!([PathSegment::Key("b".to_string())], error.path());
+    }
+
+    fn skip_document(mode: DecodingMode, bytes: &[u8]) -> Result<(), DecodingErrorKind> {
+        let mut decoder = Decoder::new(bytes).with_mode(mode);
+
+        match decoder.next_object() {
+            Ok(Some(object)) => object.skip(),
+            Ok(None) => Ok(()),
+            Err(error) => Err(error),
+        }
+        .map_err(DecodingError::into_kind)
+    }
+
+    #[test]
+    fn strict_rejects_non_canonical() {
+        let non_canonical: [&[u8]; 6] = [
+            b"i02e",
+            b"i-01e",
+            b"i-0e",
+            b"05:hello",
+            b"d1:bi1e1:ai2ee",
+            b"d1:ai1e1:ai2ee",
+        ];
+
+        for bytes in non_canonical {
+            assert!(skip_document(DecodingMode::Permissive, bytes).is_ok());
+        }
+
+        let errors = non_canonical.map(|bytes| skip_document(DecodingMode::Strict, bytes));
+
+        assert!(matches!(errors[0], Err(DecodingErrorKind::LeadingZero)));
+        assert!(matches!(errors[1], Err(DecodingErrorKind::LeadingZero)));
+        assert!(matches!(errors[2], Err(DecodingErrorKind::NegativeZero)));
+        assert!(matches!(
+            errors[3],
+            Err(DecodingErrorKind::LeadingZeroInLength)
+        ));
+        assert!(matches!(
+            &errors[4],
+            Err(DecodingErrorKind::UnsortedKey { key }) if key == "a"
+        ));
+        assert!(matches!(
+            &errors[5],
+            Err(DecodingErrorKind::DuplicateKey { key }) if key == "a"
+        ));
+    }
+
+    #[test]
+    fn lenient_collects_warnings() {
+        let mut decoder = Decoder::new(b"d1:bi-0e1:ali03eee").with_mode(DecodingMode::Lenient);
+        decoder.next_object().unwrap().unwrap().skip().unwrap();
+
+        let warnings: Vec<_> = decoder
+            .warnings()
+            .iter()
+            .map(|warning| (warning.kind(), warning.offset(), warning.path()))
+            .collect();
+
+        assert!(matches!(
+            warnings[..],
+            [
+                (
+                    DecodingErrorKind::NegativeZero,
+                    Some(4),
+                    [PathSegment::Key(_)]
+                ),
+                (
+                    DecodingErrorKind::UnsortedKey { .. },
+                    Some(8),
+                    [PathSegment::Key(_)]
+                ),
+                (
+                    DecodingErrorKind::LeadingZero,
+                    Some(12),
+                    [PathSegment::Key(_), PathSegment::Index(0)]
+                ),
+            ]
+        ));
+    }
+
+    fn limit_error(limits: DecoderLimits, bytes: &[u8]) -> [Option<(Limit, usize)>; 2] {
+        let limit = |error: DecodingError| match error.into_kind() {
+            DecodingErrorKind::LimitExceeded { limit, maximum } => Some((limit, maximum)),
+            _ => None,
+        };
+
+        let value = Decoder::new(bytes).with_limits(limits).decode_value();
+
+        let mut decoder = Decoder::new(bytes).with_limits(limits);
+        let streaming = match decoder.next_object() {
+            Ok(Some(object)) => object.skip(),
+            Ok(None) => Ok(()),
+            Err(error) => Err(error),
+        };
+
+        [value.err().and_then(limit), streaming.err().and_then(limit)]
+    }
+
+    #[test]
+    fn limits() {
+        let limits = DecoderLimits {
+            max_depth: 2,
+            max_byte_string_length: 4,
+            max_items: 2,
+            max_total_bytes: 16,
+        };
+
+        assert_eq!([None, None], limit_error(limits, b"l4:abcdli1ei2eee"));
+        assert_eq!([Some((Limit::Depth, 2)); 2], limit_error(limits, b"llleee"));
+        assert_eq!(
+            [Some((Limit::ByteStringLength, 4)); 2],
+            limit_error(limits, b"l99999999999:e")
+        );
+        assert_eq!(
+            [Some((Limit::Items, 2)); 2],
+            limit_error(limits, b"li1ei2ei3ee")
+        );
+        assert_eq!(
+            [Some((Limit::Items, 2)); 2],
+            limit_error(limits, b"d1:ai1e1:bi2e1:ci3ee")
+        );
+        assert_eq!(
+            [Some((Limit::TotalBytes, 16)); 2],
+            limit_error(limits, b"li1234567ei1234567ee")
+        );
+    }
+
+    /// Returns `depth` lists each holding a dictionary, around `i1e`.
+    fn deeply_nested(depth: usize) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(depth * 7 + 3);
+        for _ in 0..depth {
+            bytes.extend_from_slice(b"ld1:a");
+        }
+        bytes.extend_from_slice(b"i1e");
+        for _ in 0..depth {
+            bytes.extend_from_slice(b"ee");
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn decode_deeply_nested() {
+        let depth = 500_000;
+        let bytes = deeply_nested(depth);
+
+        let mut value = &Decoder::decode(&bytes).unwrap().into_owned();
+        let mut levels = 0;
+        while let Value::List(values) = value {
+            match &values[0] {
+                Value::Dictionary(values) => value = &values[b"a".as_ref()],
+                _ => panic!("expected a dictionary"),
+            }
+            levels += 1;
+        }
+
+        assert_eq!(depth, levels);
+        assert!(matches!(value, Value::Integer(1)));
+    }
+
+    #[test]
+    fn skip_deeply_nested() {
+        let mut bytes = deeply_nested(500_000);
+        bytes.extend_from_slice(b"i2e");
+
+        let mut decoder = Decoder::new(&bytes);
+        decoder.next_object().unwrap().unwrap().skip().unwrap();
+
+        assert!(matches!(
+            decoder.next_object(),
+            Ok(Some(Object::Integer(b"2", _)))
+        ));
+    }
+
+    #[test]
+    fn decode_trailing_data() {
+        assert!(matches!(
+            Decoder::decode(b"d1:ai1eeGARBAGE").map_err(DecodingError::into_kind),
+            Err(DecodingErrorKind::TrailingData { offset: 8 })
+        ));
+
+        let (value, rest) = Decoder::decode_prefix(b"d1:ai1eeGARBAGE").unwrap();
+
+        assert!(matches!(&value, Value::Dictionary(values) if values.len() == 1));
+        assert_eq!(b"GARBAGE", rest);
+    }
 }
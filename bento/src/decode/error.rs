@@ -1,8 +1,17 @@
-use std::string::FromUtf8Error;
+use std::{error::Error, fmt, str::Utf8Error, string::FromUtf8Error};
+
+/// An error raised while decoding, located in the input by its byte offset and by the path of
+/// dictionary keys and list indices leading to it.
+#[derive(Debug)]
+pub struct DecodingError {
+    kind: DecodingErrorKind,
+    offset: Option<usize>,
+    path: Vec<PathSegment>,
+}
 
 #[derive(Debug, thiserror::Error)]
-pub enum DecodingError {
-    #[error("")]
+pub enum DecodingErrorKind {
+    #[error("Dictionary key is not followed by a value")]
     MissingDictionaryValue,
     #[error("Missing field {field}")]
     MissingField { field: &'static str },
@@ -13,30 +22,225 @@ pub enum DecodingError {
         expected_object: &'static str,
         actual_object: &'static str,
     },
+    #[error("Unknown variant {variant}")]
+    UnknownVariant { variant: String },
+    #[error("Data did not match any variant of {name}")]
+    NoMatchingVariant { name: &'static str },
+    #[error("Expected a list of {expected} items")]
+    InvalidLength { expected: usize },
     #[error("Document ended to soon")]
     UnexpectedEof,
+    #[error("Unexpected data after the end of the document")]
+    TrailingData { offset: usize },
+    #[error("Unexpected byte 0x{found:02x}")]
+    UnexpectedByte { found: u8 },
+    #[error("Invalid byte string length prefix")]
+    InvalidLengthPrefix,
+    #[error("Invalid integer")]
+    InvalidInteger,
+    #[error("Integer has a leading zero")]
+    LeadingZero,
+    #[error("Negative zero")]
+    NegativeZero,
+    #[error("Byte string length has a leading zero")]
+    LeadingZeroInLength,
+    #[error("Key {key} is not sorted")]
+    UnsortedKey { key: String },
+    #[error("Duplicate key {key}")]
+    DuplicateKey { key: String },
+    #[error("Integer does not fit into {target}")]
+    IntegerOverflow { target: &'static str },
+    #[error("Failed to parse {target}: {source}")]
+    ParseFailed {
+        target: &'static str,
+        source: Box<dyn Error + Send + Sync>,
+    },
+    #[error("Exceeded the maximum {limit} of {maximum}")]
+    LimitExceeded { limit: Limit, maximum: usize },
     #[error("Invalid String")]
     InvalidString(#[from] FromUtf8Error),
-    #[error("Unknown error")]
-    Unknown,
+    #[error("Invalid str")]
+    InvalidStr(#[from] Utf8Error),
+}
+
+/// One of the [`DecoderLimits`](crate::DecoderLimits).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Depth,
+    ByteStringLength,
+    Items,
+    TotalBytes,
+}
+
+/// One step of the path to an error: the key of a dictionary or the index in a list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
 }
 
 impl DecodingError {
+    pub const fn new(kind: DecodingErrorKind) -> Self {
+        Self {
+            kind,
+            offset: None,
+            path: Vec::new(),
+        }
+    }
+
+    pub const fn kind(&self) -> &DecodingErrorKind {
+        &self.kind
+    }
+
+    pub fn into_kind(self) -> DecodingErrorKind {
+        self.kind
+    }
+
+    /// The offset of the token at which decoding failed, from the start of the input.
+    pub const fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// The keys and indices leading from the root of the document to the error.
+    pub fn path(&self) -> &[PathSegment] {
+        &self.path
+    }
+
+    /// Sets where the error happened, unless it has already been located.
+    pub(crate) fn located(
+        mut self,
+        offset: usize,
+        path: impl FnOnce() -> Vec<PathSegment>,
+    ) -> Self {
+        if self.offset.is_none() {
+            self.offset = Some(offset);
+            self.path = path();
+        }
+
+        self
+    }
+
     pub const fn missing_field(field: &'static str) -> Self {
-        Self::MissingField { field }
+        Self::new(DecodingErrorKind::MissingField { field })
     }
 
     pub const fn unexpected_field(field: String) -> Self {
-        Self::UnexpectedField { field }
+        Self::new(DecodingErrorKind::UnexpectedField { field })
+    }
+
+    pub const fn unknown_variant(variant: String) -> Self {
+        Self::new(DecodingErrorKind::UnknownVariant { variant })
+    }
+
+    pub const fn no_matching_variant(name: &'static str) -> Self {
+        Self::new(DecodingErrorKind::NoMatchingVariant { name })
+    }
+
+    pub const fn invalid_length(expected: usize) -> Self {
+        Self::new(DecodingErrorKind::InvalidLength { expected })
+    }
+
+    pub fn unsorted_key(key: &[u8]) -> Self {
+        Self::new(DecodingErrorKind::UnsortedKey {
+            key: String::from_utf8_lossy(key).into_owned(),
+        })
+    }
+
+    pub fn duplicate_key(key: &[u8]) -> Self {
+        Self::new(DecodingErrorKind::DuplicateKey {
+            key: String::from_utf8_lossy(key).into_owned(),
+        })
+    }
+
+    pub const fn trailing_data(offset: usize) -> Self {
+        Self::new(DecodingErrorKind::TrailingData { offset })
+    }
+
+    pub const fn unexpected_byte(found: u8) -> Self {
+        Self::new(DecodingErrorKind::UnexpectedByte { found })
+    }
+
+    pub const fn integer_overflow(target: &'static str) -> Self {
+        Self::new(DecodingErrorKind::IntegerOverflow { target })
+    }
+
+    pub const fn limit_exceeded(limit: Limit, maximum: usize) -> Self {
+        Self::new(DecodingErrorKind::LimitExceeded { limit, maximum })
+    }
+
+    pub fn parse_failed(target: &'static str, source: impl Error + Send + Sync + 'static) -> Self {
+        Self::new(DecodingErrorKind::ParseFailed {
+            target,
+            source: Box::new(source),
+        })
     }
 
     pub const fn unexpected_object(
         expected_object: &'static str,
         actual_object: &'static str,
     ) -> Self {
-        Self::UnexpectedObject {
+        Self::new(DecodingErrorKind::UnexpectedObject {
             expected_object,
             actual_object,
+        })
+    }
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Limit::Depth => "depth",
+            Limit::ByteStringLength => "byte string length",
+            Limit::Items => "number of items",
+            Limit::TotalBytes => "document size",
+        })
+    }
+}
+
+impl fmt::Display for DecodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+
+        if !self.path.is_empty() {
+            write!(f, " at ")?;
+
+            for (index, segment) in self.path.iter().enumerate() {
+                match segment {
+                    PathSegment::Key(key) if index == 0 => write!(f, "{}", key)?,
+                    PathSegment::Key(key) => write!(f, ".{}", key)?,
+                    PathSegment::Index(index) => write!(f, "[{}]", index)?,
+                }
+            }
+        }
+
+        if let Some(offset) = self.offset {
+            write!(f, " (offset {})", offset)?;
         }
+
+        Ok(())
+    }
+}
+
+impl Error for DecodingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.kind.source()
+    }
+}
+
+impl From<DecodingErrorKind> for DecodingError {
+    fn from(kind: DecodingErrorKind) -> Self {
+        Self::new(kind)
+    }
+}
+
+impl From<FromUtf8Error> for DecodingError {
+    fn from(error: FromUtf8Error) -> Self {
+        Self::new(error.into())
+    }
+}
+
+impl From<Utf8Error> for DecodingError {
+    fn from(error: Utf8Error) -> Self {
+        Self::new(error.into())
     }
 }
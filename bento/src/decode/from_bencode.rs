@@ -1,48 +1,141 @@
-use atoi::atoi;
+use atoi::FromRadix10SignedChecked;
 use std::{
+    borrow::Cow,
     collections::HashMap,
     hash::{BuildHasher, Hash},
     net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
 };
 
-use super::{AsString, Decoder, DecodingError, Object, Value};
+use super::{
+    AsString, Decoder, DecodingError, DecodingErrorKind, DecodingMode, Object, RawBencode, Value,
+};
 
-pub trait FromBencode<V = Self> {
-    fn from_bencode(bytes: &[u8]) -> Result<V, DecodingError> {
+/// Decodes a value out of bencode borrowed for `'de`.
+///
+/// The lifetime allows types such as `&'de [u8]`, `&'de str` or `Cow<'de, [u8]>` to borrow
+/// straight from the input instead of copying it.
+pub trait FromBencode<'de, V = Self> {
+    /// Decodes `bytes`, which must hold a single document and nothing after it.
+    fn from_bencode(bytes: &'de [u8]) -> Result<V, DecodingError> {
         let mut decoder = Decoder::new(bytes);
+        let value = Self::from_decoder(&mut decoder)?;
+        decoder.finish()?;
+
+        Ok(value)
+    }
+
+    /// Decodes `bytes`, rejecting anything but canonical bencode.
+    fn from_bencode_strict(bytes: &'de [u8]) -> Result<V, DecodingError> {
+        let mut decoder = Decoder::new(bytes).with_mode(DecodingMode::Strict);
+        let value = Self::from_decoder(&mut decoder)?;
+        decoder.finish()?;
+
+        Ok(value)
+    }
+
+    /// Decodes the document at the start of `bytes`, returning it with the bytes following it.
+    ///
+    /// This is needed when bencode is followed by other data, such as the piece of metadata
+    /// following the dictionary of a BEP 9 `ut_metadata` message.
+    fn from_bencode_prefix(bytes: &'de [u8]) -> Result<(V, &'de [u8]), DecodingError> {
+        let mut decoder = Decoder::new(bytes);
+        let value = Self::from_decoder(&mut decoder)?;
+
+        Ok((value, decoder.remaining()))
+    }
+
+    /// Decodes the next object of `decoder`.
+    fn from_decoder(decoder: &mut Decoder<'de>) -> Result<V, DecodingError> {
         let object = decoder.next_object()?;
 
-        object.map_or(Err(DecodingError::UnexpectedEof), Self::decode)
+        // The end of the input is an error of its own, no object means an `e` was read
+        object
+            .map_or(Err(DecodingError::unexpected_byte(b'e')), Self::decode)
+            .map_err(|error| decoder.locate(error))
     }
 
-    fn decode(object: Object) -> Result<V, DecodingError>;
+    fn decode(object: Object<'_, 'de>) -> Result<V, DecodingError>;
+}
+
+/// A type that can be decoded without borrowing from the input, whatever its lifetime.
+pub trait FromBencodeOwned: Sized + for<'de> FromBencode<'de> {}
+
+impl<T> FromBencodeOwned for T where T: for<'de> FromBencode<'de> {}
+
+/// Decodes a value out of the pairs of a dictionary, one pair at a time.
+///
+/// This is what allows a field to be inlined into the dictionary of its parent with
+/// `#[bento(flatten)]`. It is implemented by derived structs with named fields, which consume
+/// the keys of their own fields, and by maps, which consume every key they are offered.
+pub trait FromBencodeFields<'de>: Sized {
+    /// Holds the fields decoded so far.
+    type Builder: Default;
+
+    /// Decodes `value` into the builder if `key` belongs to this type, otherwise hands it back.
+    fn decode_field<'obj>(
+        builder: &mut Self::Builder,
+        key: &'de [u8],
+        value: Object<'obj, 'de>,
+    ) -> Result<Option<Object<'obj, 'de>>, DecodingError>;
+
+    /// Builds the value once the dictionary has been read entirely.
+    fn finish(builder: Self::Builder) -> Result<Self, DecodingError>;
+}
+
+/// Decodes the key of a dictionary entry, which is always a byte string.
+///
+/// Maps decode their keys with this rather than [`FromBencode`], so that a `Vec<u8>` key is read
+/// from the byte string itself even though a `Vec<u8>` value is a list.
+pub trait FromBencodeKey<'de>: Sized {
+    fn decode_key(key: &'de [u8]) -> Result<Self, DecodingError>;
+}
+
+impl<'de> FromBencode<'de> for Value<'de> {
+    /// Decodes the object with the mode and limits of its decoder, without recursing into
+    /// nested values.
+    fn decode(object: Object<'_, 'de>) -> Result<Self, DecodingError>
+    where
+        Self: Sized,
+    {
+        match object {
+            Object::ByteString(byte_string, _) => Ok(Value::ByteString(Cow::Borrowed(byte_string))),
+            Object::Integer(..) => object.decode().map(Value::Integer),
+            Object::List(list_decoder) => list_decoder.decode_value(),
+            Object::Dictionary(dictionary_decoder) => dictionary_decoder.decode_value(),
+        }
+    }
 }
 
-impl<'obj, 'de> FromBencode for Value<'de> {
-    fn decode(object: Object) -> Result<Self, DecodingError>
+impl<'de> FromBencode<'de> for RawBencode<'de> {
+    fn decode(object: Object<'_, 'de>) -> Result<Self, DecodingError>
     where
         Self: Sized,
     {
-        // Decoder::decode(object.as_bytes())
-        todo!()
+        object.as_bytes().map(RawBencode)
     }
 }
 
-impl FromBencode<Vec<u8>> for AsString<Vec<u8>> {
-    fn decode(object: Object) -> Result<Vec<u8>, DecodingError> {
+impl<'de> FromBencode<'de, Vec<u8>> for AsString<Vec<u8>> {
+    fn decode(object: Object<'_, 'de>) -> Result<Vec<u8>, DecodingError> {
         object.try_byte_string().map(Vec::from)
     }
 }
 
 macro_rules! impl_from_bencode_for_num {
     ($($type:ty)*) => {$(
-        impl FromBencode for $type {
+        impl<'de> FromBencode<'de> for $type {
 
-            fn decode(object: Object) -> Result<Self, DecodingError>
+            fn decode(object: Object<'_, 'de>) -> Result<Self, DecodingError>
             where
                 Self: Sized,
             {
-                atoi(object.try_integer()?).ok_or(DecodingError::Unknown)
+                let integer = object.try_integer()?;
+
+                match <$type>::from_radix_10_signed_checked(integer) {
+                    (Some(value), used) if used > 0 && used == integer.len() => Ok(value),
+                    (None, _) => Err(DecodingError::integer_overflow(stringify!($type))),
+                    _ => Err(DecodingErrorKind::InvalidInteger.into()),
+                }
             }
         }
     )*}
@@ -50,8 +143,8 @@ macro_rules! impl_from_bencode_for_num {
 
 impl_from_bencode_for_num!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
 
-impl<T: FromBencode> FromBencode for Vec<T> {
-    fn decode(object: Object) -> Result<Self, DecodingError>
+impl<'de, T: FromBencode<'de>> FromBencode<'de> for Vec<T> {
+    fn decode(object: Object<'_, 'de>) -> Result<Self, DecodingError>
     where
         Self: Sized,
     {
@@ -67,8 +160,8 @@ impl<T: FromBencode> FromBencode for Vec<T> {
     }
 }
 
-impl FromBencode for String {
-    fn decode(object: Object) -> Result<Self, DecodingError>
+impl<'de> FromBencode<'de> for String {
+    fn decode(object: Object<'_, 'de>) -> Result<Self, DecodingError>
     where
         Self: Sized,
     {
@@ -76,17 +169,53 @@ impl FromBencode for String {
     }
 }
 
+impl<'de> FromBencode<'de> for &'de [u8] {
+    fn decode(object: Object<'_, 'de>) -> Result<Self, DecodingError>
+    where
+        Self: Sized,
+    {
+        object.try_byte_string()
+    }
+}
+
+impl<'de> FromBencode<'de> for &'de str {
+    fn decode(object: Object<'_, 'de>) -> Result<Self, DecodingError>
+    where
+        Self: Sized,
+    {
+        Ok(std::str::from_utf8(object.try_byte_string()?)?)
+    }
+}
+
+impl<'de> FromBencode<'de> for Cow<'de, [u8]> {
+    fn decode(object: Object<'_, 'de>) -> Result<Self, DecodingError>
+    where
+        Self: Sized,
+    {
+        object.decode().map(Cow::Borrowed)
+    }
+}
+
+impl<'de> FromBencode<'de> for Cow<'de, str> {
+    fn decode(object: Object<'_, 'de>) -> Result<Self, DecodingError>
+    where
+        Self: Sized,
+    {
+        object.decode().map(Cow::Borrowed)
+    }
+}
+
 macro_rules! impl_from_bencode_for_from_str {
     ($($type:ty)*) => {$(
-        impl FromBencode for $type {
+        impl<'de> FromBencode<'de> for $type {
 
-            fn decode(object: Object) -> Result<Self, DecodingError>
+            fn decode(object: Object<'_, 'de>) -> Result<Self, DecodingError>
             where
                 Self: Sized,
             {
-                object.decode::<String>()?
+                object.decode::<&str>()?
                     .parse()
-                    .map_err(|_| DecodingError::Unknown)
+                    .map_err(|error| DecodingError::parse_failed(stringify!($type), error))
             }
         }
     )*}
@@ -96,13 +225,49 @@ impl_from_bencode_for_from_str!(Ipv4Addr Ipv6Addr IpAddr SocketAddrV4 SocketAddr
 #[cfg(feature = "url")]
 impl_from_bencode_for_from_str!(url::Url);
 
-impl<K, V, H> FromBencode for HashMap<K, V, H>
+impl FromBencodeKey<'_> for Vec<u8> {
+    fn decode_key(key: &[u8]) -> Result<Self, DecodingError> {
+        Ok(key.to_vec())
+    }
+}
+
+impl FromBencodeKey<'_> for String {
+    fn decode_key(key: &[u8]) -> Result<Self, DecodingError> {
+        Ok(String::from_utf8(key.to_vec())?)
+    }
+}
+
+impl<'de> FromBencodeKey<'de> for &'de [u8] {
+    fn decode_key(key: &'de [u8]) -> Result<Self, DecodingError> {
+        Ok(key)
+    }
+}
+
+impl<'de> FromBencodeKey<'de> for &'de str {
+    fn decode_key(key: &'de [u8]) -> Result<Self, DecodingError> {
+        Ok(std::str::from_utf8(key)?)
+    }
+}
+
+impl<'de> FromBencodeKey<'de> for Cow<'de, [u8]> {
+    fn decode_key(key: &'de [u8]) -> Result<Self, DecodingError> {
+        Ok(Cow::Borrowed(key))
+    }
+}
+
+impl<'de> FromBencodeKey<'de> for Cow<'de, str> {
+    fn decode_key(key: &'de [u8]) -> Result<Self, DecodingError> {
+        <&str>::decode_key(key).map(Cow::Borrowed)
+    }
+}
+
+impl<'de, K, V, H> FromBencode<'de> for HashMap<K, V, H>
 where
-    K: FromBencode + Hash + Eq,
-    V: FromBencode,
+    K: FromBencodeKey<'de> + Hash + Eq,
+    V: FromBencode<'de>,
     H: BuildHasher + Default,
 {
-    fn decode(object: Object) -> Result<Self, DecodingError>
+    fn decode(object: Object<'_, 'de>) -> Result<Self, DecodingError>
     where
         Self: Sized,
     {
@@ -110,7 +275,7 @@ where
         let mut result = HashMap::default();
 
         while let Some((key, value)) = dict.next_pair()? {
-            let key = Object::ByteString(key).decode()?;
+            let key = K::decode_key(key)?;
             let value = value.decode()?;
 
             result.insert(key, value);
@@ -120,13 +285,13 @@ where
     }
 }
 
-impl<K, V, H> FromBencode for indexmap::IndexMap<K, V, H>
+impl<'de, K, V, H> FromBencode<'de> for indexmap::IndexMap<K, V, H>
 where
-    K: FromBencode + Hash + Eq,
-    V: FromBencode,
+    K: FromBencodeKey<'de> + Hash + Eq,
+    V: FromBencode<'de>,
     H: BuildHasher + Default,
 {
-    fn decode(object: Object) -> Result<Self, DecodingError>
+    fn decode(object: Object<'_, 'de>) -> Result<Self, DecodingError>
     where
         Self: Sized,
     {
@@ -134,7 +299,7 @@ where
         let mut result = Self::default();
 
         while let Some((key, value)) = dict.next_pair()? {
-            let key = Object::ByteString(key).decode()?;
+            let key = K::decode_key(key)?;
             let value = value.decode()?;
 
             result.insert(key, value);
@@ -144,11 +309,100 @@ where
     }
 }
 
-impl<T: FromBencode> FromBencode for Option<T> {
-    fn decode(object: Object) -> Result<Self, DecodingError>
+impl<'de, K, V, H> FromBencodeFields<'de> for HashMap<K, V, H>
+where
+    K: FromBencodeKey<'de> + Hash + Eq,
+    V: FromBencode<'de>,
+    H: BuildHasher + Default,
+{
+    type Builder = Self;
+
+    fn decode_field<'obj>(
+        builder: &mut Self,
+        key: &'de [u8],
+        value: Object<'obj, 'de>,
+    ) -> Result<Option<Object<'obj, 'de>>, DecodingError> {
+        builder.insert(K::decode_key(key)?, value.decode()?);
+        Ok(None)
+    }
+
+    fn finish(builder: Self) -> Result<Self, DecodingError> {
+        Ok(builder)
+    }
+}
+
+impl<'de, K, V, H> FromBencodeFields<'de> for indexmap::IndexMap<K, V, H>
+where
+    K: FromBencodeKey<'de> + Hash + Eq,
+    V: FromBencode<'de>,
+    H: BuildHasher + Default,
+{
+    type Builder = Self;
+
+    fn decode_field<'obj>(
+        builder: &mut Self,
+        key: &'de [u8],
+        value: Object<'obj, 'de>,
+    ) -> Result<Option<Object<'obj, 'de>>, DecodingError> {
+        builder.insert(K::decode_key(key)?, value.decode()?);
+        Ok(None)
+    }
+
+    fn finish(builder: Self) -> Result<Self, DecodingError> {
+        Ok(builder)
+    }
+}
+
+impl<'de, T: FromBencode<'de>> FromBencode<'de> for Option<T> {
+    fn decode(object: Object<'_, 'de>) -> Result<Self, DecodingError>
     where
         Self: Sized,
     {
         object.decode().map(Option::Some)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_parse_failed() {
+        assert!(matches!(
+            Ipv4Addr::from_bencode(b"9:127.0.0.x").map_err(DecodingError::into_kind),
+            Err(DecodingErrorKind::ParseFailed {
+                target: "Ipv4Addr",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn decode_trailing_data() -> Result<(), DecodingError> {
+        let bytes = b"4:spam\x00\x01\x02";
+
+        assert!(matches!(
+            String::from_bencode(bytes).map_err(DecodingError::into_kind),
+            Err(DecodingErrorKind::TrailingData { offset: 6 })
+        ));
+
+        let (spam, rest) = String::from_bencode_prefix(bytes)?;
+
+        assert_eq!("spam", spam);
+        assert_eq!(b"\x00\x01\x02", rest);
+
+        Ok(())
+    }
+
+    #[test]
+    fn decode_missing_object() {
+        assert!(matches!(
+            u8::from_bencode(b"e").map_err(DecodingError::into_kind),
+            Err(DecodingErrorKind::UnexpectedByte { found: b'e' })
+        ));
+        assert!(matches!(
+            u8::from_bencode(b"").map_err(DecodingError::into_kind),
+            Err(DecodingErrorKind::UnexpectedEof)
+        ));
+    }
+}
@@ -2,10 +2,12 @@ mod decoder;
 mod error;
 mod from_bencode;
 mod object;
+mod visitor;
 
-pub(crate) use crate::{AsString, Token, Value};
+pub(crate) use crate::{AsString, RawBencode, Token, Value};
 
-pub use decoder::{Decoder, DictionaryDecoder, ListDecoder};
-pub use error::DecodingError;
-pub use from_bencode::FromBencode;
-pub use object::Object;
+pub use decoder::{Decoder, DecoderLimits, DecodingMode, DictionaryDecoder, ListDecoder, Tokens};
+pub use error::{DecodingError, DecodingErrorKind, Limit, PathSegment};
+pub use from_bencode::{FromBencode, FromBencodeFields, FromBencodeKey, FromBencodeOwned};
+pub use object::{Object, ObjectKind};
+pub use visitor::Visitor;
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use crate::FromBencode;
 
 use super::{
@@ -5,27 +7,52 @@ use super::{
     DecodingError,
 };
 
+/// The kind of an object, as seen before reading it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    ByteString,
+    Integer,
+    List,
+    Dictionary,
+    /// The end of the enclosing list or dictionary.
+    End,
+}
+
+/// An object read from the input, lists and dictionaries being read item by item.
+///
+/// Byte strings and integers hold their content, along with the object as it is encoded in the
+/// input when it was read by a [`Decoder`](crate::Decoder).
 pub enum Object<'obj, 'de: 'obj> {
-    ByteString(&'de [u8]),
-    Integer(&'de [u8]),
+    ByteString(&'de [u8], Option<&'de [u8]>),
+    Integer(&'de [u8], Option<&'de [u8]>),
     List(ListDecoder<'obj, 'de>),
     Dictionary(DictionaryDecoder<'obj, 'de>),
 }
 
 impl<'obj, 'de: 'obj> Object<'obj, 'de> {
-    pub const fn as_bytes(self) -> &'de [u8] {
-        match self {
-            Object::ByteString(byte_string) => byte_string,
-            Object::Integer(integer) => integer,
-            Object::List(list_decoder) => list_decoder.as_bytes(),
-            Object::Dictionary(dictionary_decoder) => dictionary_decoder.as_bytes(),
-        }
+    /// Returns the encoded object, skipping whatever has not been read of it.
+    ///
+    /// Objects are borrowed from the input as they were written. Byte strings and integers
+    /// without an encoding are framed again.
+    pub fn as_bytes(self) -> Result<Cow<'de, [u8]>, DecodingError> {
+        Ok(match self {
+            Object::ByteString(_, Some(encoded)) | Object::Integer(_, Some(encoded)) => {
+                Cow::Borrowed(encoded)
+            }
+            Object::ByteString(byte_string, None) => {
+                let length = byte_string.len().to_string();
+                Cow::Owned([length.as_bytes(), b":", byte_string].concat())
+            }
+            Object::Integer(integer, None) => Cow::Owned([b"i", integer, b"e"].concat()),
+            Object::List(list_decoder) => Cow::Borrowed(list_decoder.as_bytes()?),
+            Object::Dictionary(dictionary_decoder) => Cow::Borrowed(dictionary_decoder.as_bytes()?),
+        })
     }
 
     pub const fn name(&self) -> &'static str {
         match *self {
-            Object::ByteString(_) => "ByteString",
-            Object::Integer(_) => "Integer",
+            Object::ByteString(..) => "ByteString",
+            Object::Integer(..) => "Integer",
             Object::List(_) => "List",
             Object::Dictionary(_) => "Dictionary",
         }
@@ -33,36 +60,36 @@ impl<'obj, 'de: 'obj> Object<'obj, 'de> {
 
     pub const fn byte_string(self) -> Option<&'de [u8]> {
         match self {
-            Object::ByteString(byte_string) => Some(byte_string),
+            Object::ByteString(byte_string, _) => Some(byte_string),
             _ => None,
         }
     }
 
     pub const fn is_byte_string(&self) -> bool {
-        matches!(*self, Object::ByteString(_))
+        matches!(*self, Object::ByteString(..))
     }
 
     pub const fn try_byte_string(self) -> Result<&'de [u8], DecodingError> {
         match self {
-            Object::ByteString(byte_string) => Ok(byte_string),
+            Object::ByteString(byte_string, _) => Ok(byte_string),
             _ => Err(DecodingError::unexpected_object("ByteString", self.name())),
         }
     }
 
     pub const fn integer(self) -> Option<&'de [u8]> {
         match self {
-            Object::Integer(integer) => Some(integer),
+            Object::Integer(integer, _) => Some(integer),
             _ => None,
         }
     }
 
     pub const fn is_integer(&self) -> bool {
-        matches!(*self, Object::Integer(_))
+        matches!(*self, Object::Integer(..))
     }
 
     pub const fn try_integer(self) -> Result<&'de [u8], DecodingError> {
         match self {
-            Object::Integer(integer) => Ok(integer),
+            Object::Integer(integer, _) => Ok(integer),
             _ => Err(DecodingError::unexpected_object("Integer", self.name())),
         }
     }
@@ -105,14 +132,43 @@ impl<'obj, 'de: 'obj> Object<'obj, 'de> {
 
     pub fn skip(self) -> Result<(), DecodingError> {
         match self {
-            Object::ByteString(_) => Ok(()),
-            Object::Integer(_) => Ok(()),
+            Object::ByteString(..) => Ok(()),
+            Object::Integer(..) => Ok(()),
             Object::List(mut list_decoder) => list_decoder.skip(),
             Object::Dictionary(mut dictionary_decoder) => dictionary_decoder.skip(),
         }
     }
 
-    pub fn decode<T: FromBencode>(self) -> Result<T, DecodingError> {
+    pub fn decode<T: FromBencode<'de>>(self) -> Result<T, DecodingError> {
         T::decode(self)
     }
+
+    /// Runs `f` on a copy of this object, leaving the object itself unconsumed.
+    ///
+    /// This allows inspecting an object, for example to find the tag of an enum, before deciding
+    /// how to decode it.
+    pub fn lookahead<T, F>(&self, f: F) -> Result<T, DecodingError>
+    where
+        F: FnOnce(Object<'_, 'de>) -> Result<T, DecodingError>,
+    {
+        match self {
+            Object::ByteString(byte_string, encoded) => {
+                f(Object::ByteString(byte_string, *encoded))
+            }
+            Object::Integer(integer, encoded) => f(Object::Integer(integer, *encoded)),
+            Object::List(list_decoder) => {
+                let (mut decoder, start, end) = list_decoder.fork();
+                f(Object::List(ListDecoder::new_at(&mut decoder, start, end)))
+            }
+            Object::Dictionary(dictionary_decoder) => {
+                let (mut decoder, start, end, pending_key) = dictionary_decoder.fork();
+                f(Object::Dictionary(DictionaryDecoder::new_at(
+                    &mut decoder,
+                    start,
+                    end,
+                    pending_key,
+                )))
+            }
+        }
+    }
 }
@@ -0,0 +1,37 @@
+use super::DecodingError;
+
+/// Receives the objects of a document as they are read by [`Decoder::visit`], without building
+/// them.
+///
+/// Every method does nothing by default, so that a visitor only implements the events it
+/// watches. An error returned by a method stops the decoding and is located where the event
+/// occurred.
+///
+/// [`Decoder::visit`]: super::Decoder::visit
+pub trait Visitor<'de> {
+    fn visit_byte_string(&mut self, _byte_string: &'de [u8]) -> Result<(), DecodingError> {
+        Ok(())
+    }
+
+    fn visit_integer(&mut self, _integer: &'de [u8]) -> Result<(), DecodingError> {
+        Ok(())
+    }
+
+    fn begin_list(&mut self) -> Result<(), DecodingError> {
+        Ok(())
+    }
+
+    fn begin_dictionary(&mut self) -> Result<(), DecodingError> {
+        Ok(())
+    }
+
+    /// Called for each key of a dictionary, before its value is visited.
+    fn visit_key(&mut self, _key: &'de [u8]) -> Result<(), DecodingError> {
+        Ok(())
+    }
+
+    /// Called at the end of a list or a dictionary.
+    fn end(&mut self) -> Result<(), DecodingError> {
+        Ok(())
+    }
+}
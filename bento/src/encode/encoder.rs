@@ -16,6 +16,10 @@ pub struct Encoder {
     pub(crate) bytes: Vec<u8>,
 }
 pub struct DictionaryEncoder<'a>(&'a mut Encoder);
+/// Buffers the pairs of a dictionary so they can be given in any order.
+pub struct SortingDictionaryEncoder {
+    pairs: Vec<(Vec<u8>, Vec<u8>)>,
+}
 
 impl Encoder {
     pub fn new() -> Encoder {
@@ -58,6 +62,11 @@ impl Encoder {
         self.emit_token(Token::ByteString(value.as_ref()))
     }
 
+    /// Emit bytes that are already bencoded, as they are
+    pub fn emit_raw<T: AsRef<[u8]>>(&mut self, value: T) {
+        self.bytes.extend_from_slice(value.as_ref())
+    }
+
     /// Emit an integer
     pub fn emit_integer<T: PrintableInteger>(&mut self, integer: T) {
         self.emit_token(Token::Integer(integer.to_string().as_bytes()))
@@ -80,6 +89,27 @@ impl Encoder {
         content_callback(DictionaryEncoder(self));
         self.emit_token(Token::End)
     }
+
+    /// Emit a dictionary whose pairs can be given in any order, they are sorted by key before
+    /// being written. When a key is given more than once only the first pair is kept.
+    pub fn emit_sorting_dictionary<F>(&mut self, content_callback: F)
+    where
+        F: FnOnce(&mut SortingDictionaryEncoder),
+    {
+        let mut dictionary = SortingDictionaryEncoder { pairs: Vec::new() };
+        content_callback(&mut dictionary);
+
+        // The sort is stable so the first pair given for a key is the one kept
+        dictionary.pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
+        dictionary.pairs.dedup_by(|(a, _), (b, _)| a == b);
+
+        self.emit_token(Token::DictionaryStart);
+        for (key, value) in dictionary.pairs {
+            self.emit_token(Token::ByteString(&key));
+            self.bytes.extend_from_slice(&value);
+        }
+        self.emit_token(Token::End)
+    }
 }
 
 impl Default for Encoder {
@@ -97,4 +127,33 @@ impl<'a> DictionaryEncoder<'a> {
         self.0.emit_token(Token::ByteString(key));
         value.encode(self.0);
     }
+
+    /// Emit a key followed by a value written by the given callback
+    pub fn emit_pair_with<F>(&mut self, key: &[u8], value_callback: F)
+    where
+        F: FnOnce(&mut Encoder),
+    {
+        self.0.emit_token(Token::ByteString(key));
+        value_callback(self.0);
+    }
+}
+
+impl SortingDictionaryEncoder {
+    /// Emit a key/value pair
+    pub fn emit_pair<E>(&mut self, key: &[u8], value: E)
+    where
+        E: ToBencode,
+    {
+        self.emit_pair_with(key, |encoder| value.encode(encoder))
+    }
+
+    /// Emit a key followed by a value written by the given callback
+    pub fn emit_pair_with<F>(&mut self, key: &[u8], value_callback: F)
+    where
+        F: FnOnce(&mut Encoder),
+    {
+        let mut encoder = Encoder::new();
+        value_callback(&mut encoder);
+        self.pairs.push((key.to_vec(), encoder.bytes));
+    }
 }
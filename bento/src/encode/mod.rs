@@ -1,7 +1,7 @@
 mod encoder;
 mod to_bencode;
 
-pub(crate) use crate::{AsString, Token};
+pub(crate) use crate::{AsString, RawBencode, Token, Value};
 
-pub use encoder::{DictionaryEncoder, Encoder};
-pub use to_bencode::ToBencode;
+pub use encoder::{DictionaryEncoder, Encoder, SortingDictionaryEncoder};
+pub use to_bencode::{ToBencode, ToBencodeFields};
@@ -1,6 +1,9 @@
-use std::collections::{LinkedList, VecDeque};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, LinkedList, VecDeque},
+};
 
-use super::{AsString, Encoder};
+use super::{AsString, Encoder, RawBencode, SortingDictionaryEncoder, Token, Value};
 
 pub trait ToBencode {
     fn to_bencode(&self) -> Vec<u8>
@@ -15,6 +18,29 @@ pub trait ToBencode {
     }
 
     fn encode(&self, encoder: &mut Encoder);
+
+    /// Encodes a slice of values, as a list unless the type overrides it.
+    ///
+    /// `u8` encodes its slices as byte strings, which makes `&[u8]` the counterpart of its
+    /// [`FromBencode`](crate::FromBencode) impl.
+    fn encode_slice(items: &[Self], encoder: &mut Encoder)
+    where
+        Self: Sized,
+    {
+        encoder.emit_list(|e| {
+            for item in items {
+                e.emit(item);
+            }
+        });
+    }
+}
+
+/// Encodes a value as pairs of its parent's dictionary.
+///
+/// This is the counterpart of [`FromBencodeFields`](crate::FromBencodeFields) used by
+/// `#[bento(flatten)]`, the pairs are sorted together with the ones of the parent.
+pub trait ToBencodeFields {
+    fn encode_fields(&self, dictionary: &mut SortingDictionaryEncoder);
 }
 
 // Forwarding impls
@@ -25,7 +51,7 @@ impl<'a, E: 'a + ToBencode + Sized> ToBencode for &'a E {
 }
 
 // Base type impls
-impl<'a> ToBencode for &'a str {
+impl ToBencode for &str {
     fn encode(&self, encoder: &mut Encoder) {
         encoder.emit_byte_string(self)
     }
@@ -37,6 +63,18 @@ impl ToBencode for String {
     }
 }
 
+impl ToBencode for Cow<'_, str> {
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.emit_byte_string(self)
+    }
+}
+
+impl ToBencode for Cow<'_, [u8]> {
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.emit_byte_array(self)
+    }
+}
+
 macro_rules! impl_encodable_integer {
     ($($type:ty)*) => {$(
         impl ToBencode for $type {
@@ -47,7 +85,17 @@ macro_rules! impl_encodable_integer {
     )*}
 }
 
-impl_encodable_integer!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+impl_encodable_integer!(u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+impl ToBencode for u8 {
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.emit_integer(*self)
+    }
+
+    fn encode_slice(items: &[Self], encoder: &mut Encoder) {
+        encoder.emit_byte_array(items)
+    }
+}
 
 macro_rules! impl_encodable_iterable {
     ($($type:ident)*) => {$(
@@ -77,16 +125,60 @@ where
     }
 }
 
-impl<'a, T> ToBencode for &'a [T]
+impl ToBencode for RawBencode<'_> {
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.emit_raw(self.as_bytes())
+    }
+}
+
+/// Encodes nested values one after the other instead of recursively, as they are decoded, and
+/// sorts the keys of dictionaries.
+impl ToBencode for Value<'_> {
+    fn encode(&self, encoder: &mut Encoder) {
+        enum Item<'v, 'a> {
+            Value(&'v Value<'a>),
+            Key(&'v [u8]),
+            End,
+        }
+
+        let mut stack = vec![Item::Value(self)];
+
+        while let Some(item) = stack.pop() {
+            match item {
+                Item::Value(Value::ByteString(byte_string)) => {
+                    encoder.emit_token(Token::ByteString(byte_string))
+                }
+                Item::Value(Value::Integer(integer)) => encoder.emit_integer(*integer),
+                Item::Value(Value::List(values)) => {
+                    encoder.emit_token(Token::ListStart);
+                    stack.push(Item::End);
+                    stack.extend(values.iter().rev().map(Item::Value));
+                }
+                Item::Value(Value::Dictionary(values)) => {
+                    encoder.emit_token(Token::DictionaryStart);
+                    stack.push(Item::End);
+
+                    let mut pairs = values.iter().collect::<Vec<_>>();
+                    pairs.sort_unstable_by_key(|&(key, _)| key);
+
+                    for (key, value) in pairs.into_iter().rev() {
+                        stack.push(Item::Value(value));
+                        stack.push(Item::Key(key));
+                    }
+                }
+                Item::Key(key) => encoder.emit_token(Token::ByteString(key)),
+                Item::End => encoder.emit_token(Token::End),
+            }
+        }
+    }
+}
+
+impl<T> ToBencode for &[T]
 where
     T: ToBencode,
 {
     fn encode(&self, encoder: &mut Encoder) {
-        encoder.emit_list(|e| {
-            for item in *self {
-                e.emit(item);
-            }
-        });
+        T::encode_slice(self, encoder)
     }
 }
 
@@ -96,3 +188,27 @@ impl ToBencode for url::Url {
         encoder.emit_byte_string(self)
     }
 }
+
+impl<K, V, H> ToBencodeFields for HashMap<K, V, H>
+where
+    K: AsRef<[u8]>,
+    V: ToBencode,
+{
+    fn encode_fields(&self, dictionary: &mut SortingDictionaryEncoder) {
+        for (key, value) in self {
+            dictionary.emit_pair(key.as_ref(), value);
+        }
+    }
+}
+
+impl<K, V, H> ToBencodeFields for indexmap::IndexMap<K, V, H>
+where
+    K: AsRef<[u8]>,
+    V: ToBencode,
+{
+    fn encode_fields(&self, dictionary: &mut SortingDictionaryEncoder) {
+        for (key, value) in self {
+            dictionary.emit_pair(key.as_ref(), value);
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Computes the info hash of a torrent from its original bytes.
+//!
+//! The `info` dictionary is hashed exactly as it appears in the file, so the hash is right even
+//! when the torrent is not canonical bencode.
+
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use crate::{Decoder, DecodingError, Object};
+
+/// Returns the bytes of the value of the `info` key of the top-level dictionary of `torrent`.
+///
+/// The other values are skipped without being decoded, whatever the order of the keys.
+pub fn info_bytes(torrent: &[u8]) -> Result<&[u8], DecodingError> {
+    info_bytes_from_decoder(&mut Decoder::new(torrent))
+}
+
+/// Returns the bytes of the value of the `info` key of the dictionary read next by `decoder`,
+/// following its mode and limits.
+///
+/// The decoder is left right after the `info` dictionary.
+pub fn info_bytes_from_decoder<'de>(
+    decoder: &mut Decoder<'de>,
+) -> Result<&'de [u8], DecodingError> {
+    let info = match decoder.next_object()? {
+        Some(torrent) => find_info(torrent),
+        None => Err(DecodingError::unexpected_byte(b'e')),
+    };
+
+    info.map_err(|error| decoder.locate(error))
+}
+
+fn find_info<'de>(torrent: Object<'_, 'de>) -> Result<&'de [u8], DecodingError> {
+    match torrent.try_dictionary()?.find(b"info")? {
+        Some(info) => info.try_dictionary()?.as_bytes(),
+        None => Err(DecodingError::missing_field("info")),
+    }
+}
+
+/// Returns the SHA-1 hash of the `info` dictionary, which identifies v1 torrents.
+pub fn info_hash_v1(torrent: &[u8]) -> Result<[u8; 20], DecodingError> {
+    Ok(Sha1::digest(info_bytes(torrent)?).into())
+}
+
+/// Returns the SHA-256 hash of the `info` dictionary, which identifies v2 torrents.
+pub fn info_hash_v2(torrent: &[u8]) -> Result<[u8; 32], DecodingError> {
+    Ok(Sha256::digest(info_bytes(torrent)?).into())
+}
+
+/// Returns the SHA-256 hash of the `info` dictionary truncated to 20 bytes, the form used by
+/// v2 torrents where v1 info hashes are expected, such as the DHT or tracker announces.
+pub fn info_hash_v2_truncated(torrent: &[u8]) -> Result<[u8; 20], DecodingError> {
+    let hash = info_hash_v2(torrent)?;
+    let mut truncated = [0; 20];
+    truncated.copy_from_slice(&hash[..20]);
+
+    Ok(truncated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DecoderLimits, DecodingErrorKind, Limit};
+
+    #[test]
+    fn info_bytes_of_non_canonical_torrent() {
+        let torrent = b"d4:infod6:lengthi1e4:name1:ae8:announce3:urle";
+
+        assert_eq!(b"d6:lengthi1e4:name1:ae", info_bytes(torrent).unwrap());
+        assert_eq!(
+            Sha1::digest(b"d6:lengthi1e4:name1:ae")[..],
+            info_hash_v1(torrent).unwrap()
+        );
+        assert_eq!(
+            info_hash_v2(torrent).unwrap()[..20],
+            info_hash_v2_truncated(torrent).unwrap()
+        );
+    }
+
+    #[test]
+    fn info_bytes_missing() {
+        assert!(matches!(
+            info_bytes(b"d8:announce3:urle").map_err(DecodingError::into_kind),
+            Err(DecodingErrorKind::MissingField { field: "info" })
+        ));
+    }
+
+    #[test]
+    fn info_bytes_errors() {
+        let error = info_bytes(b"d8:announce3:url4:infoli1eee").unwrap_err();
+        assert_eq!(Some(22), error.offset());
+        assert!(matches!(
+            error.into_kind(),
+            DecodingErrorKind::UnexpectedObject { .. }
+        ));
+
+        let limits = DecoderLimits {
+            max_depth: 2,
+            ..DecoderLimits::unlimited()
+        };
+        let mut decoder = Decoder::new(b"d4:infod1:ald1:ai1eeeee").with_limits(limits);
+
+        assert!(matches!(
+            info_bytes_from_decoder(&mut decoder).map_err(DecodingError::into_kind),
+            Err(DecodingErrorKind::LimitExceeded {
+                limit: Limit::Depth,
+                maximum: 2
+            })
+        ));
+    }
+}
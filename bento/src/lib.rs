@@ -1,14 +1,45 @@
 mod decode;
 mod encode;
+#[cfg(feature = "info-hash")]
+mod info_hash;
+mod value;
 
 #[cfg(feature = "derive")]
 pub use bento_derive::{Bencode, FromBencode, ToBencode};
-pub use decode::{Decoder, DecodingError, DictionaryDecoder, FromBencode, ListDecoder, Object};
-pub use encode::{DictionaryEncoder, Encoder, ToBencode};
+pub use decode::{
+    Decoder, DecoderLimits, DecodingError, DecodingErrorKind, DecodingMode, DictionaryDecoder,
+    FromBencode, FromBencodeFields, FromBencodeKey, FromBencodeOwned, Limit, ListDecoder, Object,
+    ObjectKind, PathSegment, Tokens, Visitor,
+};
+pub use encode::{
+    DictionaryEncoder, Encoder, SortingDictionaryEncoder, ToBencode, ToBencodeFields,
+};
+#[cfg(feature = "info-hash")]
+pub use info_hash::{
+    info_bytes, info_bytes_from_decoder, info_hash_v1, info_hash_v2, info_hash_v2_truncated,
+};
+pub use value::{Dictionary, List, Value};
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct AsString<I>(pub I);
 
+/// A complete encoded value kept as it is, for example to hash the `info` dictionary of a
+/// torrent exactly as it was received.
+///
+/// See [`Object::as_bytes`] for how the bytes are obtained.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RawBencode<'a>(pub std::borrow::Cow<'a, [u8]>);
+
+impl RawBencode<'_> {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn into_owned(self) -> RawBencode<'static> {
+        RawBencode(std::borrow::Cow::Owned(self.0.into_owned()))
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Token<'a> {
     ByteString(&'a [u8]),
@@ -18,10 +49,14 @@ pub enum Token<'a> {
     End,
 }
 
-#[derive(Debug)]
-pub enum Value<'a> {
-    ByteString(&'a [u8]),
-    Integer(&'a [u8]),
-    List(Vec<Value<'a>>),
-    Dictionary(indexmap::IndexMap<&'a [u8], Value<'a>>),
+impl Token<'_> {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Token::ByteString(_) => "ByteString",
+            Token::Integer(_) => "Integer",
+            Token::ListStart => "List",
+            Token::DictionaryStart => "Dictionary",
+            Token::End => "End",
+        }
+    }
 }
@@ -0,0 +1,897 @@
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    mem,
+    ops::{Deref, DerefMut, Index, IndexMut},
+    str,
+};
+
+use indexmap::IndexMap;
+
+/// Any bencode object, either borrowed from the input it was decoded from or owned.
+///
+/// Integers are decoded as `i64`, byte strings and keys are borrowed from the input and a
+/// document can be kept after its input is gone with [`Value::into_owned`].
+///
+/// Lists and dictionaries hold their content in a [`List`] and a [`Dictionary`], which drop
+/// nested values one after the other instead of recursively.
+#[derive(Debug)]
+pub enum Value<'a> {
+    ByteString(Cow<'a, [u8]>),
+    Integer(i64),
+    List(List<'a>),
+    Dictionary(Dictionary<'a>),
+}
+
+/// The items of a [`Value::List`], used as a `Vec`.
+///
+/// Dropping a list drops the nested values one after the other instead of recursively, so that
+/// dropping a deep value can not overflow the stack.
+#[derive(Default)]
+pub struct List<'a>(Vec<Value<'a>>);
+
+/// The pairs of a [`Value::Dictionary`], used as an `IndexMap`.
+///
+/// As with [`List`], dropping a dictionary does not recurse into nested values.
+#[derive(Default)]
+pub struct Dictionary<'a>(IndexMap<Cow<'a, [u8]>, Value<'a>>);
+
+impl<'a> Value<'a> {
+    /// Copies the byte strings and keys borrowed from the input, without recursing into nested
+    /// values.
+    pub fn into_owned(mut self) -> Value<'static> {
+        let mut root = match PartialValue::new(&mut self) {
+            Ok(partial_value) => partial_value,
+            Err(value) => return value,
+        };
+        let mut stack = Vec::new();
+
+        loop {
+            let partial_value = stack.last_mut().unwrap_or(&mut root);
+
+            match partial_value.next() {
+                Some(mut value) => match PartialValue::new(&mut value) {
+                    Ok(nested) => stack.push(nested),
+                    Err(value) => partial_value.push(value),
+                },
+                None => match stack.pop() {
+                    Some(nested) => stack.last_mut().unwrap_or(&mut root).push(nested.finish()),
+                    None => return root.finish(),
+                },
+            }
+        }
+    }
+
+    /// Inserts a pair into a dictionary, returning the previous value of `key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is not a dictionary.
+    pub fn insert<K, V>(&mut self, key: K, value: V) -> Option<Value<'a>>
+    where
+        K: Into<Cow<'a, [u8]>>,
+        V: Into<Value<'a>>,
+    {
+        match self {
+            Value::Dictionary(values) => values.insert(key.into(), value.into()),
+            value => panic!("cannot insert into a {}", value.name()),
+        }
+    }
+
+    /// Removes `key` from a dictionary, keeping the order of the other keys, and returns its
+    /// value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is not a dictionary.
+    pub fn remove(&mut self, key: &[u8]) -> Option<Value<'a>> {
+        match self {
+            Value::Dictionary(values) => values.shift_remove(key),
+            value => panic!("cannot remove from a {}", value.name()),
+        }
+    }
+
+    /// Appends a value to a list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is not a list.
+    pub fn push<V: Into<Value<'a>>>(&mut self, value: V) {
+        match self {
+            Value::List(values) => values.push(value.into()),
+            value => panic!("cannot push into a {}", value.name()),
+        }
+    }
+
+    /// Returns the value of `key` if this is a dictionary containing it.
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<&Value<'a>> {
+        self.as_dict()?.get(key.as_ref())
+    }
+
+    pub fn get_mut<K: AsRef<[u8]>>(&mut self, key: K) -> Option<&mut Value<'a>> {
+        self.as_dict_mut()?.get_mut(key.as_ref())
+    }
+
+    /// Looks up a nested value, such as `/info/files/3/length`, with the syntax of JSON Pointer.
+    ///
+    /// Each segment is a key of a dictionary or the index of an item in a list, in which `~1`
+    /// stands for `/` and `~0` for `~`. An empty pointer designates the value itself.
+    pub fn pointer(&self, pointer: &str) -> Option<&Value<'a>> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .try_fold(self, |value, segment| match value {
+                Value::Dictionary(values) => values.get(unescape(segment).as_bytes()),
+                Value::List(values) => values.get(list_index(segment)?),
+                Value::ByteString(_) | Value::Integer(_) => None,
+            })
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value<'a>> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .try_fold(self, |value, segment| match value {
+                Value::Dictionary(values) => values.get_mut(unescape(segment).as_bytes()),
+                Value::List(values) => values.get_mut(list_index(segment)?),
+                Value::ByteString(_) | Value::Integer(_) => None,
+            })
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::ByteString(byte_string) => Some(byte_string),
+            _ => None,
+        }
+    }
+
+    /// Returns the byte string if it is valid UTF-8.
+    pub fn as_str(&self) -> Option<&str> {
+        str::from_utf8(self.as_bytes()?).ok()
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Integer(integer) => Some(integer),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&Vec<Value<'a>>> {
+        match self {
+            Value::List(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_dict(&self) -> Option<&IndexMap<Cow<'a, [u8]>, Value<'a>>> {
+        match self {
+            Value::Dictionary(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes_mut(&mut self) -> Option<&mut Cow<'a, [u8]>> {
+        match self {
+            Value::ByteString(byte_string) => Some(byte_string),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64_mut(&mut self) -> Option<&mut i64> {
+        match self {
+            Value::Integer(integer) => Some(integer),
+            _ => None,
+        }
+    }
+
+    pub fn as_list_mut(&mut self) -> Option<&mut Vec<Value<'a>>> {
+        match self {
+            Value::List(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_dict_mut(&mut self) -> Option<&mut IndexMap<Cow<'a, [u8]>, Value<'a>>> {
+        match self {
+            Value::Dictionary(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Value::ByteString(_) => "ByteString",
+            Value::Integer(_) => "Integer",
+            Value::List(_) => "List",
+            Value::Dictionary(_) => "Dictionary",
+        }
+    }
+
+    /// Orders the kinds of values for [`Ord`].
+    const fn rank(&self) -> u8 {
+        match self {
+            Value::ByteString(_) => 0,
+            Value::Integer(_) => 1,
+            Value::List(_) => 2,
+            Value::Dictionary(_) => 3,
+        }
+    }
+}
+
+/// Decodes the escapes of a segment of a pointer.
+fn unescape(segment: &str) -> Cow<'_, str> {
+    if segment.contains('~') {
+        Cow::Owned(segment.replace("~1", "/").replace("~0", "~"))
+    } else {
+        Cow::Borrowed(segment)
+    }
+}
+
+/// Parses a segment of a pointer as the index of a list, which is made of digits only.
+fn list_index(segment: &str) -> Option<usize> {
+    if segment.is_empty() || !segment.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    segment.parse().ok()
+}
+
+/// Returns the pairs of a dictionary in the order of canonical bencode.
+fn sorted_pairs<'v, 'a>(
+    values: &'v IndexMap<Cow<'a, [u8]>, Value<'a>>,
+) -> Vec<(&'v [u8], &'v Value<'a>)> {
+    let mut pairs = values
+        .iter()
+        .map(|(key, value)| (key.as_ref(), value))
+        .collect::<Vec<_>>();
+    pairs.sort_unstable_by_key(|&(key, _)| key);
+    pairs
+}
+
+/// A part of a value left to visit when walking it without recursion.
+enum Part<'v, 'a> {
+    Value(&'v Value<'a>),
+    Key(&'v [u8]),
+    /// Punctuation, only used by `Display`
+    Text(&'static str),
+}
+
+/// A comparison left to make when comparing values without recursion.
+enum Comparison<'v, 'a> {
+    Values(&'v Value<'a>, &'v Value<'a>),
+    Keys(&'v [u8], &'v [u8]),
+    Lengths(usize, usize),
+}
+
+/// Orders byte strings before integers, lists and dictionaries. Values of the same kind are
+/// compared by content: integers by their value, byte strings by their bytes, and lists and
+/// dictionaries item by item, the pairs of a dictionary being taken in the order of canonical
+/// bencode, that is sorted by key.
+impl Ord for Value<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let mut stack = vec![Comparison::Values(self, other)];
+
+        while let Some(comparison) = stack.pop() {
+            let ordering = match comparison {
+                Comparison::Values(Value::ByteString(a), Value::ByteString(b)) => a.cmp(b),
+                Comparison::Values(Value::Integer(a), Value::Integer(b)) => a.cmp(b),
+                Comparison::Values(Value::List(a), Value::List(b)) => {
+                    stack.push(Comparison::Lengths(a.len(), b.len()));
+                    stack.extend(a.iter().zip(b).rev().map(|(a, b)| Comparison::Values(a, b)));
+                    Ordering::Equal
+                }
+                Comparison::Values(Value::Dictionary(a), Value::Dictionary(b)) => {
+                    stack.push(Comparison::Lengths(a.len(), b.len()));
+
+                    let pairs = sorted_pairs(a).into_iter().zip(sorted_pairs(b));
+                    for ((a_key, a), (b_key, b)) in pairs.rev() {
+                        stack.push(Comparison::Values(a, b));
+                        stack.push(Comparison::Keys(a_key, b_key));
+                    }
+
+                    Ordering::Equal
+                }
+                Comparison::Values(a, b) => a.rank().cmp(&b.rank()),
+                Comparison::Keys(a, b) => a.cmp(b),
+                Comparison::Lengths(a, b) => a.cmp(&b),
+            };
+
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+
+        Ordering::Equal
+    }
+}
+
+impl PartialOrd for Value<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Dictionaries with the same pairs are equal whatever the order of their keys.
+impl PartialEq for Value<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Value<'_> {}
+
+/// Hashes the pairs of dictionaries sorted by key, as they are compared.
+impl Hash for Value<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut stack = vec![Part::Value(self)];
+
+        while let Some(part) = stack.pop() {
+            let value = match part {
+                Part::Value(value) => value,
+                Part::Key(key) => {
+                    key.hash(state);
+                    continue;
+                }
+                Part::Text(_) => continue,
+            };
+
+            value.rank().hash(state);
+            match value {
+                Value::ByteString(byte_string) => byte_string.hash(state),
+                Value::Integer(integer) => integer.hash(state),
+                Value::List(values) => {
+                    values.len().hash(state);
+                    stack.extend(values.iter().rev().map(Part::Value));
+                }
+                Value::Dictionary(values) => {
+                    values.len().hash(state);
+                    for (key, value) in sorted_pairs(values).into_iter().rev() {
+                        stack.push(Part::Value(value));
+                        stack.push(Part::Key(key));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Prints a value on one line, such as `{"name": "bento", "pieces": <0a1b>, "files": [1, 2]}`.
+///
+/// Byte strings are quoted when they are valid UTF-8 and printed in hexadecimal between angle
+/// brackets otherwise. Dictionaries keep the order of their keys.
+impl fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut stack = vec![Part::Value(self)];
+
+        while let Some(part) = stack.pop() {
+            match part {
+                Part::Value(Value::ByteString(byte_string)) => write_byte_string(f, byte_string)?,
+                Part::Value(Value::Integer(integer)) => write!(f, "{}", integer)?,
+                Part::Value(Value::List(values)) => {
+                    f.write_str("[")?;
+                    stack.push(Part::Text("]"));
+                    for (index, value) in values.iter().enumerate().rev() {
+                        stack.push(Part::Value(value));
+                        if index > 0 {
+                            stack.push(Part::Text(", "));
+                        }
+                    }
+                }
+                Part::Value(Value::Dictionary(values)) => {
+                    f.write_str("{")?;
+                    stack.push(Part::Text("}"));
+                    for (index, (key, value)) in values.iter().enumerate().rev() {
+                        stack.push(Part::Value(value));
+                        stack.push(Part::Text(": "));
+                        stack.push(Part::Key(key));
+                        if index > 0 {
+                            stack.push(Part::Text(", "));
+                        }
+                    }
+                }
+                Part::Key(key) => write_byte_string(f, key)?,
+                Part::Text(text) => f.write_str(text)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn write_byte_string(f: &mut fmt::Formatter<'_>, byte_string: &[u8]) -> fmt::Result {
+    match str::from_utf8(byte_string) {
+        Ok(string) => write!(f, "{:?}", string),
+        Err(_) => {
+            f.write_str("<")?;
+            for byte in byte_string {
+                write!(f, "{:02x}", byte)?;
+            }
+            f.write_str(">")
+        }
+    }
+}
+
+impl<'a> List<'a> {
+    pub const fn new() -> Self {
+        List(Vec::new())
+    }
+
+    pub fn into_vec(mut self) -> Vec<Value<'a>> {
+        mem::take(&mut self.0)
+    }
+}
+
+impl<'a> Dictionary<'a> {
+    pub fn new() -> Self {
+        Dictionary(IndexMap::new())
+    }
+
+    pub fn into_map(mut self) -> IndexMap<Cow<'a, [u8]>, Value<'a>> {
+        mem::take(&mut self.0)
+    }
+}
+
+impl Drop for List<'_> {
+    fn drop(&mut self) {
+        drop_values(mem::take(&mut self.0));
+    }
+}
+
+impl Drop for Dictionary<'_> {
+    fn drop(&mut self) {
+        drop_values(self.0.drain(..).map(|(_, value)| value).collect());
+    }
+}
+
+/// Drops `stack`, moving the content of nested lists and dictionaries onto it before they are
+/// dropped themselves.
+fn drop_values(mut stack: Vec<Value<'_>>) {
+    while let Some(value) = stack.pop() {
+        match value {
+            Value::List(mut values) => stack.append(&mut values.0),
+            Value::Dictionary(mut values) => {
+                stack.extend(values.0.drain(..).map(|(_, value)| value))
+            }
+            Value::ByteString(_) | Value::Integer(_) => {}
+        }
+    }
+}
+
+impl<'a> Deref for List<'a> {
+    type Target = Vec<Value<'a>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for List<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'a> Deref for Dictionary<'a> {
+    type Target = IndexMap<Cow<'a, [u8]>, Value<'a>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Dictionary<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl fmt::Debug for List<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Debug for Dictionary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<'a> From<Vec<Value<'a>>> for List<'a> {
+    fn from(values: Vec<Value<'a>>) -> Self {
+        List(values)
+    }
+}
+
+impl<'a> From<IndexMap<Cow<'a, [u8]>, Value<'a>>> for Dictionary<'a> {
+    fn from(values: IndexMap<Cow<'a, [u8]>, Value<'a>>) -> Self {
+        Dictionary(values)
+    }
+}
+
+impl<'a> FromIterator<Value<'a>> for List<'a> {
+    fn from_iter<I: IntoIterator<Item = Value<'a>>>(values: I) -> Self {
+        List(values.into_iter().collect())
+    }
+}
+
+impl<'a> FromIterator<(Cow<'a, [u8]>, Value<'a>)> for Dictionary<'a> {
+    fn from_iter<I: IntoIterator<Item = (Cow<'a, [u8]>, Value<'a>)>>(pairs: I) -> Self {
+        Dictionary(pairs.into_iter().collect())
+    }
+}
+
+impl<'a> IntoIterator for List<'a> {
+    type Item = Value<'a>;
+    type IntoIter = std::vec::IntoIter<Value<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+impl<'v, 'a> IntoIterator for &'v List<'a> {
+    type Item = &'v Value<'a>;
+    type IntoIter = std::slice::Iter<'v, Value<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'v, 'a> IntoIterator for &'v mut List<'a> {
+    type Item = &'v mut Value<'a>;
+    type IntoIter = std::slice::IterMut<'v, Value<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter_mut()
+    }
+}
+
+impl<'a> IntoIterator for Dictionary<'a> {
+    type Item = (Cow<'a, [u8]>, Value<'a>);
+    type IntoIter = indexmap::map::IntoIter<Cow<'a, [u8]>, Value<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_map().into_iter()
+    }
+}
+
+impl<'v, 'a> IntoIterator for &'v Dictionary<'a> {
+    type Item = (&'v Cow<'a, [u8]>, &'v Value<'a>);
+    type IntoIter = indexmap::map::Iter<'v, Cow<'a, [u8]>, Value<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'v, 'a> IntoIterator for &'v mut Dictionary<'a> {
+    type Item = (&'v Cow<'a, [u8]>, &'v mut Value<'a>);
+    type IntoIter = indexmap::map::IterMut<'v, Cow<'a, [u8]>, Value<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter_mut()
+    }
+}
+
+/// A list or a dictionary being copied by [`Value::into_owned`], along with the values left to
+/// copy and the key of the value being copied.
+enum PartialValue<'a> {
+    List(Vec<Value<'static>>, std::vec::IntoIter<Value<'a>>),
+    Dictionary(
+        IndexMap<Cow<'static, [u8]>, Value<'static>>,
+        indexmap::map::IntoIter<Cow<'a, [u8]>, Value<'a>>,
+        Option<Cow<'static, [u8]>>,
+    ),
+}
+
+impl<'a> PartialValue<'a> {
+    /// Starts copying a list or a dictionary, or returns the copy of any other value.
+    fn new(value: &mut Value<'a>) -> Result<Self, Value<'static>> {
+        match value {
+            Value::ByteString(byte_string) => Err(Value::ByteString(Cow::Owned(
+                mem::take(byte_string).into_owned(),
+            ))),
+            Value::Integer(integer) => Err(Value::Integer(*integer)),
+            Value::List(values) => Ok(PartialValue::List(
+                Vec::with_capacity(values.len()),
+                mem::take(values).into_iter(),
+            )),
+            Value::Dictionary(values) => Ok(PartialValue::Dictionary(
+                IndexMap::with_capacity(values.len()),
+                mem::take(values).into_iter(),
+                None,
+            )),
+        }
+    }
+
+    fn next(&mut self) -> Option<Value<'a>> {
+        match self {
+            PartialValue::List(_, values) => values.next(),
+            PartialValue::Dictionary(_, values, key) => values.next().map(|(next_key, value)| {
+                *key = Some(Cow::Owned(next_key.into_owned()));
+                value
+            }),
+        }
+    }
+
+    fn push(&mut self, value: Value<'static>) {
+        match self {
+            PartialValue::List(values, _) => values.push(value),
+            PartialValue::Dictionary(values, _, key) => {
+                if let Some(key) = key.take() {
+                    values.insert(key, value);
+                }
+            }
+        }
+    }
+
+    fn finish(self) -> Value<'static> {
+        match self {
+            PartialValue::List(values, _) => Value::List(values.into()),
+            PartialValue::Dictionary(values, ..) => Value::Dictionary(values.into()),
+        }
+    }
+}
+
+impl From<i64> for Value<'_> {
+    fn from(integer: i64) -> Self {
+        Value::Integer(integer)
+    }
+}
+
+impl<'a> From<&'a [u8]> for Value<'a> {
+    fn from(byte_string: &'a [u8]) -> Self {
+        Value::ByteString(Cow::Borrowed(byte_string))
+    }
+}
+
+impl From<Vec<u8>> for Value<'_> {
+    fn from(byte_string: Vec<u8>) -> Self {
+        Value::ByteString(Cow::Owned(byte_string))
+    }
+}
+
+impl<'a> From<&'a str> for Value<'a> {
+    fn from(string: &'a str) -> Self {
+        Value::ByteString(Cow::Borrowed(string.as_bytes()))
+    }
+}
+
+impl From<String> for Value<'_> {
+    fn from(string: String) -> Self {
+        Value::ByteString(Cow::Owned(string.into_bytes()))
+    }
+}
+
+impl<'a> From<Vec<Value<'a>>> for Value<'a> {
+    fn from(values: Vec<Value<'a>>) -> Self {
+        Value::List(values.into())
+    }
+}
+
+impl<'a> From<List<'a>> for Value<'a> {
+    fn from(values: List<'a>) -> Self {
+        Value::List(values)
+    }
+}
+
+impl<'a> From<IndexMap<Cow<'a, [u8]>, Value<'a>>> for Value<'a> {
+    fn from(values: IndexMap<Cow<'a, [u8]>, Value<'a>>) -> Self {
+        Value::Dictionary(values.into())
+    }
+}
+
+impl<'a> From<Dictionary<'a>> for Value<'a> {
+    fn from(values: Dictionary<'a>) -> Self {
+        Value::Dictionary(values)
+    }
+}
+
+/// Returns the value of a key of a dictionary.
+///
+/// # Panics
+///
+/// Panics if this is not a dictionary or if the key is missing, see [`Value::get`] otherwise.
+impl<'a> Index<&str> for Value<'a> {
+    type Output = Value<'a>;
+
+    fn index(&self, key: &str) -> &Value<'a> {
+        match self.get(key) {
+            Some(value) => value,
+            None => panic!("no key {:?} in the {}", key, self.name()),
+        }
+    }
+}
+
+impl IndexMut<&str> for Value<'_> {
+    fn index_mut(&mut self, key: &str) -> &mut Self {
+        let name = self.name();
+        match self.get_mut(key) {
+            Some(value) => value,
+            None => panic!("no key {:?} in the {}", key, name),
+        }
+    }
+}
+
+/// Returns an item of a list.
+///
+/// # Panics
+///
+/// Panics if this is not a list or if the index is out of bounds.
+impl<'a> Index<usize> for Value<'a> {
+    type Output = Value<'a>;
+
+    fn index(&self, index: usize) -> &Value<'a> {
+        match self.as_list().and_then(|values| values.get(index)) {
+            Some(value) => value,
+            None => panic!("no item {} in the {}", index, self.name()),
+        }
+    }
+}
+
+impl IndexMut<usize> for Value<'_> {
+    fn index_mut(&mut self, index: usize) -> &mut Self {
+        let name = self.name();
+        match self.as_list_mut().and_then(|values| values.get_mut(index)) {
+            Some(value) => value,
+            None => panic!("no item {} in the {}", index, name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Decoder, DecodingError, DecodingErrorKind, ToBencode};
+
+    #[test]
+    fn integer_overflow() {
+        assert!(matches!(
+            Decoder::decode(b"i-9223372036854775808e").unwrap(),
+            Value::Integer(i64::MIN)
+        ));
+        assert!(matches!(
+            Decoder::decode(b"li9223372036854775808ee").map_err(DecodingError::into_kind),
+            Err(DecodingErrorKind::IntegerOverflow { target: "i64" })
+        ));
+    }
+
+    #[test]
+    fn equality_and_hash() {
+        use std::collections::hash_map::DefaultHasher;
+
+        let hash = |value: &Value| {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        };
+        let sorted = Decoder::decode(b"d1:ai1e1:bli2eee").unwrap();
+        let unsorted = Decoder::decode(b"d1:bli2ee1:ai1ee").unwrap();
+
+        assert_eq!(sorted, unsorted);
+        assert_eq!(hash(&sorted), hash(&unsorted));
+        assert_eq!(Value::Integer(7), Decoder::decode(b"i007e").unwrap());
+        assert_ne!(sorted, Decoder::decode(b"d1:ai1e1:bli3eee").unwrap());
+    }
+
+    #[test]
+    fn ordering() {
+        let values = [
+            "3:abc", "3:abd", "i-1e", "i2e", "i10e", "le", "li1ee", "li1ei0ee", "li2ee", "de",
+            "d1:ai2ee", "d1:bi1ee",
+        ]
+        .map(|bytes| Decoder::decode(bytes.as_bytes()).unwrap());
+
+        for pair in values.windows(2) {
+            assert!(pair[0] < pair[1], "{} < {}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn display() {
+        let value = Decoder::decode(b"d4:name5:bento6:pieces2:\x0a\xff5:filesli1ei2eee").unwrap();
+
+        assert_eq!(
+            r#"{"name": "bento", "pieces": <0aff>, "files": [1, 2]}"#,
+            value.to_string()
+        );
+    }
+
+    #[test]
+    fn access() {
+        let bytes = b"d4:infod5:filesld6:lengthi1eed6:lengthi2eee4:name5:bentoe3:a/bi3ee";
+        let mut value = Decoder::decode(bytes).unwrap();
+
+        assert_eq!(Some("bento"), value["info"]["name"].as_str());
+        assert_eq!(Some(2), value["info"]["files"][1]["length"].as_i64());
+        assert_eq!(
+            Some(2),
+            value
+                .pointer("/info/files/1/length")
+                .and_then(Value::as_i64)
+        );
+        assert_eq!(Some(3), value.pointer("/a~1b").and_then(Value::as_i64));
+        assert_eq!(Some(&value), value.pointer(""));
+        assert!(value.pointer("/info/files/2").is_none());
+        assert!(value.pointer("/info/files/+1").is_none());
+        assert!(value.pointer("info").is_none());
+        assert!(value.get("missing").is_none());
+        assert!(value["info"].as_list().is_none());
+        assert_eq!(2, value["info"]["files"].as_list().unwrap().len());
+        assert_eq!(2, value["info"].as_dict().unwrap().len());
+        assert_eq!(Some(b"bento".as_ref()), value["info"]["name"].as_bytes());
+
+        *value.pointer_mut("/info/files/0/length").unwrap() = Value::Integer(4);
+        value["info"]["files"].push(Vec::<Value>::new());
+        *value["a/b"].as_i64_mut().unwrap() += 1;
+        value.get_mut("info").unwrap().remove(b"name");
+
+        assert_eq!(
+            b"d3:a/bi4e4:infod5:filesld6:lengthi4eed6:lengthi2eeleeee".as_ref(),
+            value.to_bencode()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "no key \"missing\" in the Dictionary")]
+    fn index_missing_key() {
+        let value = Decoder::decode(b"de").unwrap();
+        let _ = &value["missing"];
+    }
+
+    #[test]
+    fn into_owned() {
+        let bytes = b"d4:infod6:lengthi1e4:name1:ae4:listli1e2:abee".to_vec();
+        let value = Decoder::decode(&bytes).unwrap().into_owned();
+        drop(bytes);
+
+        assert_eq!(
+            b"d4:infod6:lengthi1e4:name1:ae4:listli1e2:abee".as_ref(),
+            value.to_bencode()
+        );
+    }
+
+    #[test]
+    fn build_document() {
+        let mut info = Value::Dictionary(Dictionary::new());
+        info.insert("name".as_bytes(), "bento");
+        info.insert("length".as_bytes(), 1024);
+
+        let mut announce_list = Value::List(List::new());
+        announce_list.push(vec![Value::from("udp://tracker")]);
+
+        let mut torrent = Value::Dictionary(Dictionary::new());
+        torrent.insert("info".as_bytes(), info);
+        torrent.insert("announce-list".as_bytes(), announce_list);
+        torrent.insert("comment".as_bytes(), String::from("removed"));
+
+        assert!(matches!(
+            torrent.remove(b"comment"),
+            Some(Value::ByteString(_))
+        ));
+        assert!(torrent.remove(b"comment").is_none());
+        assert_eq!(
+            b"d13:announce-listll13:udp://trackeree4:infod6:lengthi1024e4:name5:bentoee".as_ref(),
+            torrent.to_bencode()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot remove from a List")]
+    fn remove_from_list() {
+        Value::List(List::new()).remove(b"comment");
+    }
+}
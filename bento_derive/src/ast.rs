@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+
+use syn::{ext::IdentExt, DeriveInput, Error, Generics, Ident, Path, Type};
+
+use crate::attr::{ContainerAttributes, DefaultValue, FieldAttributes, VariantAttributes};
+
+/// A parsed description of a type deriving the bento traits.
+///
+/// Both directions are generated from the same description so the keys used for decoding and
+/// encoding a type can never disagree.
+pub struct Container<'a> {
+    pub ident: &'a Ident,
+    pub generics: &'a Generics,
+    pub data: Data<'a>,
+    /// Whether keys that do not belong to any field are rejected instead of skipped.
+    pub deny_unknown_fields: bool,
+}
+
+pub enum Data<'a> {
+    /// Named fields are stored as a dictionary, unnamed fields as a list.
+    Struct(Fields<'a>),
+    /// A struct with a single field, stored exactly as that field.
+    Transparent(Fields<'a>),
+    Enum(Tagging, Vec<Variant<'a>>),
+}
+
+pub enum Fields<'a> {
+    Unit,
+    Named(Vec<Field<'a>>),
+    Unnamed(Vec<&'a Type>),
+}
+
+/// How the variant of an enum is represented.
+pub enum Tagging {
+    /// A dictionary with a single key, the name of the variant, holding its content.
+    /// Unit variants are encoded as a byte string holding their name instead.
+    External,
+    /// The name of the variant is stored under the given key, next to the fields of the variant.
+    Internal(String),
+    /// Only the content is encoded, when decoding every variant is tried in turn.
+    Untagged,
+}
+
+pub struct Variant<'a> {
+    pub ident: &'a Ident,
+    /// The name this variant is encoded as.
+    pub name: String,
+    /// Additional names accepted when decoding.
+    pub aliases: Vec<String>,
+    pub fields: Fields<'a>,
+}
+
+pub struct Field<'a> {
+    pub ident: &'a Ident,
+    pub ty: &'a Type,
+    /// The dictionary key this field is stored under.
+    pub key: String,
+    /// Additional keys accepted when decoding.
+    pub aliases: Vec<String>,
+    /// Optional fields decode to `None` when missing and are left out when `None`.
+    pub optional: bool,
+    pub default: DefaultValue,
+    /// Skipped fields are neither decoded nor encoded.
+    pub skip: bool,
+    pub skip_encoding_if: Option<Path>,
+    /// Flattened fields are decoded from and encoded into the dictionary of their parent, see
+    /// `FromBencodeFields`.
+    pub flatten: bool,
+}
+
+impl<'a> Container<'a> {
+    pub fn from_ast(input: &'a DeriveInput) -> Result<Self, Error> {
+        let attributes = ContainerAttributes::from_ast(&input.attrs)?;
+
+        let data = match &input.data {
+            syn::Data::Struct(data) => {
+                if attributes.tag.is_some() || attributes.untagged {
+                    return Err(Error::new_spanned(
+                        input,
+                        "`tag` and `untagged` can only be used on enums",
+                    ));
+                }
+
+                let fields = Fields::from_ast(&data.fields)?;
+
+                if attributes.transparent {
+                    if data.fields.len() != 1 {
+                        return Err(Error::new_spanned(
+                            input,
+                            "`transparent` requires a struct with exactly one field",
+                        ));
+                    }
+
+                    if let Fields::Named(named) = &fields {
+                        if named[0].skip || named[0].flatten {
+                            return Err(Error::new_spanned(
+                                named[0].ident,
+                                "the field of a `transparent` struct can not be skipped or flattened",
+                            ));
+                        }
+                    }
+
+                    Data::Transparent(fields)
+                } else if let Fields::Unit = fields {
+                    return Err(Error::new_spanned(
+                        input,
+                        "bento derives do not support unit structs",
+                    ));
+                } else {
+                    Data::Struct(fields)
+                }
+            }
+            syn::Data::Enum(data) => {
+                if attributes.transparent {
+                    return Err(Error::new_spanned(
+                        input,
+                        "`transparent` can only be used on structs",
+                    ));
+                }
+
+                let tagging = match (attributes.tag, attributes.untagged) {
+                    (None, false) => Tagging::External,
+                    (Some(tag), false) => Tagging::Internal(tag),
+                    (None, true) => Tagging::Untagged,
+                    (Some(_), true) => {
+                        return Err(Error::new_spanned(
+                            input,
+                            "`tag` and `untagged` can not be used together",
+                        ))
+                    }
+                };
+
+                let variants = data
+                    .variants
+                    .iter()
+                    .map(|variant| {
+                        let attributes = VariantAttributes::from_ast(&variant.attrs)?;
+
+                        Ok(Variant {
+                            ident: &variant.ident,
+                            name: attributes
+                                .rename
+                                .unwrap_or_else(|| variant.ident.unraw().to_string()),
+                            aliases: attributes.aliases,
+                            fields: Fields::from_ast(&variant.fields)?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+
+                check_variants(&tagging, &variants)?;
+
+                Data::Enum(tagging, variants)
+            }
+            syn::Data::Union(_) => {
+                return Err(Error::new_spanned(
+                    input,
+                    "bento derives do not support unions",
+                ))
+            }
+        };
+
+        Ok(Self {
+            ident: &input.ident,
+            generics: &input.generics,
+            data,
+            deny_unknown_fields: attributes.deny_unknown_fields,
+        })
+    }
+}
+
+impl<'a> Fields<'a> {
+    fn from_ast(fields: &'a syn::Fields) -> Result<Self, Error> {
+        Ok(match fields {
+            syn::Fields::Named(fields) => {
+                let fields = fields
+                    .named
+                    .iter()
+                    .map(Field::from_ast)
+                    .collect::<Result<Vec<_>, Error>>()?;
+
+                check_keys(&fields)?;
+
+                Fields::Named(fields)
+            }
+            syn::Fields::Unnamed(fields) => {
+                Fields::Unnamed(fields.unnamed.iter().map(|field| &field.ty).collect())
+            }
+            syn::Fields::Unit => Fields::Unit,
+        })
+    }
+}
+
+/// Named fields that are encoded as a pair, in the order their keys must appear in a dictionary.
+///
+/// BEP 3 requires keys to be sorted as raw byte strings, since they are known at expansion time
+/// the order can be fixed here once and for all.
+pub fn sorted_fields<'b, 'a>(fields: &'b [Field<'a>]) -> Vec<&'b Field<'a>> {
+    let mut fields: Vec<_> = fields
+        .iter()
+        .filter(|field| !field.skip && !field.flatten)
+        .collect();
+    fields.sort_by(|a, b| a.key.as_bytes().cmp(b.key.as_bytes()));
+    fields
+}
+
+impl<'a> Variant<'a> {
+    /// Every name this variant is decoded from.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.name.as_str()).chain(self.aliases.iter().map(String::as_str))
+    }
+}
+
+impl<'a> Field<'a> {
+    fn from_ast(field: &'a syn::Field) -> Result<Self, Error> {
+        // Named fields always have an identifier
+        let ident = field.ident.as_ref().unwrap();
+        let attributes = FieldAttributes::from_ast(&field.attrs)?;
+
+        if attributes.flatten
+            && (attributes.rename.is_some()
+                || !attributes.aliases.is_empty()
+                || !matches!(attributes.default, DefaultValue::None)
+                || attributes.skip_encoding_if.is_some())
+        {
+            return Err(Error::new_spanned(
+                ident,
+                "`flatten` can not be combined with `rename`, `alias`, `default` or `skip_encoding_if`",
+            ));
+        }
+
+        Ok(Field {
+            ident,
+            ty: &field.ty,
+            key: attributes
+                .rename
+                .unwrap_or_else(|| ident.unraw().to_string()),
+            aliases: attributes.aliases,
+            optional: is_option(&field.ty),
+            default: attributes.default,
+            skip: attributes.skip,
+            skip_encoding_if: attributes.skip_encoding_if,
+            flatten: attributes.flatten,
+        })
+    }
+
+    /// Every key this field is decoded from.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.key.as_str()).chain(self.aliases.iter().map(String::as_str))
+    }
+}
+
+/// Rejects two fields being decoded from the same key.
+fn check_keys(fields: &[Field]) -> Result<(), Error> {
+    let mut seen = HashMap::new();
+
+    for field in fields.iter().filter(|field| !field.skip && !field.flatten) {
+        for key in field.keys() {
+            if let Some(other) = seen.insert(key, field.ident) {
+                return Err(Error::new_spanned(
+                    field.ident,
+                    format!("key `{}` is already used by field `{}`", key, other),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Rejects two variants sharing a name and shapes the tagging can not represent.
+fn check_variants(tagging: &Tagging, variants: &[Variant]) -> Result<(), Error> {
+    let mut seen = HashMap::new();
+
+    for variant in variants {
+        if let Fields::Named(fields) = &variant.fields {
+            if let Some(field) = fields.iter().find(|field| field.flatten) {
+                return Err(Error::new_spanned(
+                    field.ident,
+                    "`flatten` is only supported on the fields of structs",
+                ));
+            }
+        }
+
+        for name in variant.names() {
+            if let Some(other) = seen.insert(name, variant.ident) {
+                return Err(Error::new_spanned(
+                    variant.ident,
+                    format!("name `{}` is already used by variant `{}`", name, other),
+                ));
+            }
+        }
+
+        if let Tagging::Internal(tag) = tagging {
+            match &variant.fields {
+                Fields::Unit => {}
+                Fields::Named(fields) => {
+                    if let Some(field) = fields
+                        .iter()
+                        .find(|field| !field.skip && field.keys().any(|key| key == tag))
+                    {
+                        return Err(Error::new_spanned(
+                            field.ident,
+                            format!("key `{}` is already used as the tag", tag),
+                        ));
+                    }
+                }
+                Fields::Unnamed(_) => {
+                    return Err(Error::new_spanned(
+                        variant.ident,
+                        "internally tagged enums only support unit and struct variants",
+                    ))
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(type_path) if type_path.qself.is_none() => type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
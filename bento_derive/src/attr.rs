@@ -0,0 +1,203 @@
+use syn::{Attribute, Error, Lit, LitStr, Meta, NestedMeta, Path};
+
+/// How a field is filled in when its key is missing from the dictionary.
+pub enum DefaultValue {
+    /// The field is required, unless it is an `Option`.
+    None,
+    /// `#[bento(default)]`, uses `Default::default()`.
+    Default,
+    /// `#[bento(default = "path")]`, calls the given function.
+    Path(Path),
+}
+
+/// The `#[bento(...)]` attributes that can be placed on a struct or enum.
+pub struct ContainerAttributes {
+    pub tag: Option<String>,
+    pub untagged: bool,
+    pub transparent: bool,
+    pub deny_unknown_fields: bool,
+}
+
+impl ContainerAttributes {
+    pub fn from_ast(attributes: &[Attribute]) -> Result<Self, Error> {
+        let mut result = Self {
+            tag: None,
+            untagged: false,
+            transparent: false,
+            deny_unknown_fields: false,
+        };
+
+        for meta in bento_meta_items(attributes)? {
+            match &meta {
+                Meta::NameValue(pair) if pair.path.is_ident("tag") => {
+                    if result.tag.is_some() {
+                        return Err(Error::new_spanned(meta, "duplicate bento attribute `tag`"));
+                    }
+                    result.tag = Some(string_literal(&pair.lit)?.value());
+                }
+                Meta::Path(path) if path.is_ident("untagged") => {
+                    result.untagged = true;
+                }
+                Meta::Path(path) if path.is_ident("transparent") => {
+                    result.transparent = true;
+                }
+                Meta::Path(path) if path.is_ident("deny_unknown_fields") => {
+                    result.deny_unknown_fields = true;
+                }
+                _ => {
+                    return Err(Error::new_spanned(
+                        meta,
+                        "unknown bento container attribute",
+                    ))
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// The `#[bento(...)]` attributes that can be placed on an enum variant.
+pub struct VariantAttributes {
+    pub rename: Option<String>,
+    pub aliases: Vec<String>,
+}
+
+impl VariantAttributes {
+    pub fn from_ast(attributes: &[Attribute]) -> Result<Self, Error> {
+        let mut result = Self {
+            rename: None,
+            aliases: Vec::new(),
+        };
+
+        for meta in bento_meta_items(attributes)? {
+            match &meta {
+                Meta::NameValue(pair) if pair.path.is_ident("rename") => {
+                    if result.rename.is_some() {
+                        return Err(Error::new_spanned(
+                            meta,
+                            "duplicate bento attribute `rename`",
+                        ));
+                    }
+                    result.rename = Some(string_literal(&pair.lit)?.value());
+                }
+                Meta::NameValue(pair) if pair.path.is_ident("alias") => {
+                    result.aliases.push(string_literal(&pair.lit)?.value());
+                }
+                _ => return Err(Error::new_spanned(meta, "unknown bento variant attribute")),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// The `#[bento(...)]` attributes that can be placed on a field.
+pub struct FieldAttributes {
+    pub rename: Option<String>,
+    pub aliases: Vec<String>,
+    pub default: DefaultValue,
+    pub skip: bool,
+    pub skip_encoding_if: Option<Path>,
+    pub flatten: bool,
+}
+
+impl FieldAttributes {
+    pub fn from_ast(attributes: &[Attribute]) -> Result<Self, Error> {
+        let mut result = Self {
+            rename: None,
+            aliases: Vec::new(),
+            default: DefaultValue::None,
+            skip: false,
+            skip_encoding_if: None,
+            flatten: false,
+        };
+
+        for meta in bento_meta_items(attributes)? {
+            match &meta {
+                Meta::NameValue(pair) if pair.path.is_ident("rename") => {
+                    if result.rename.is_some() {
+                        return Err(Error::new_spanned(
+                            meta,
+                            "duplicate bento attribute `rename`",
+                        ));
+                    }
+                    result.rename = Some(string_literal(&pair.lit)?.value());
+                }
+                Meta::NameValue(pair) if pair.path.is_ident("alias") => {
+                    result.aliases.push(string_literal(&pair.lit)?.value());
+                }
+                Meta::Path(path) if path.is_ident("default") => {
+                    if !matches!(result.default, DefaultValue::None) {
+                        return Err(Error::new_spanned(
+                            meta,
+                            "duplicate bento attribute `default`",
+                        ));
+                    }
+                    result.default = DefaultValue::Default;
+                }
+                Meta::NameValue(pair) if pair.path.is_ident("default") => {
+                    if !matches!(result.default, DefaultValue::None) {
+                        return Err(Error::new_spanned(
+                            meta,
+                            "duplicate bento attribute `default`",
+                        ));
+                    }
+                    result.default = DefaultValue::Path(string_literal(&pair.lit)?.parse()?);
+                }
+                Meta::Path(path) if path.is_ident("skip") => {
+                    result.skip = true;
+                }
+                Meta::Path(path) if path.is_ident("flatten") => {
+                    result.flatten = true;
+                }
+                Meta::NameValue(pair) if pair.path.is_ident("skip_encoding_if") => {
+                    if result.skip_encoding_if.is_some() {
+                        return Err(Error::new_spanned(
+                            meta,
+                            "duplicate bento attribute `skip_encoding_if`",
+                        ));
+                    }
+                    result.skip_encoding_if = Some(string_literal(&pair.lit)?.parse()?);
+                }
+                _ => return Err(Error::new_spanned(meta, "unknown bento field attribute")),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// Collects the items of every `#[bento(...)]` attribute, ignoring all other attributes.
+fn bento_meta_items(attributes: &[Attribute]) -> Result<Vec<Meta>, Error> {
+    let mut items = Vec::new();
+
+    for attribute in attributes {
+        if !attribute.path.is_ident("bento") {
+            continue;
+        }
+
+        match attribute.parse_meta()? {
+            Meta::List(list) => {
+                for nested in list.nested {
+                    match nested {
+                        NestedMeta::Meta(meta) => items.push(meta),
+                        NestedMeta::Lit(lit) => {
+                            return Err(Error::new_spanned(lit, "expected a bento attribute"))
+                        }
+                    }
+                }
+            }
+            meta => return Err(Error::new_spanned(meta, "expected #[bento(...)]")),
+        }
+    }
+
+    Ok(items)
+}
+
+fn string_literal(lit: &Lit) -> Result<&LitStr, Error> {
+    match lit {
+        Lit::Str(string) => Ok(string),
+        _ => Err(Error::new_spanned(lit, "expected a string literal")),
+    }
+}
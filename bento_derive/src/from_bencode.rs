@@ -0,0 +1,628 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse_quote, GenericParam, Generics, Ident, LifetimeDef, LitByteStr, Type};
+
+use crate::{
+    ast::{Container, Data, Field, Fields, Tagging, Variant},
+    attr::DefaultValue,
+};
+
+pub fn expand(container: &Container) -> TokenStream {
+    let name = container.ident;
+
+    let generics = add_trait_bounds(container.generics.clone());
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = container.generics.split_for_impl();
+
+    let deny_unknown_fields = container.deny_unknown_fields;
+
+    let body = match &container.data {
+        Data::Struct(Fields::Named(fields)) => return expand_named(container, fields),
+        Data::Struct(fields) => {
+            let value = decode_fields(fields, quote!(Self), None, deny_unknown_fields);
+            quote!(::std::result::Result::Ok(#value))
+        }
+        Data::Transparent(Fields::Named(fields)) => {
+            let ident = fields[0].ident;
+            quote!(::std::result::Result::Ok(Self { #ident: object.decode()? }))
+        }
+        Data::Transparent(_) => quote!(::std::result::Result::Ok(Self(object.decode()?))),
+        Data::Enum(Tagging::External, variants) => decode_external(variants, deny_unknown_fields),
+        Data::Enum(Tagging::Internal(tag), variants) => {
+            decode_internal(tag, variants, deny_unknown_fields)
+        }
+        Data::Enum(Tagging::Untagged, variants) => {
+            decode_untagged(name, variants, deny_unknown_fields)
+        }
+    };
+
+    quote! {
+        impl #impl_generics ::bento::FromBencode<'__de> for #name #ty_generics #where_clause {
+            fn decode(
+                object: ::bento::Object<'_, '__de>,
+            ) -> ::std::result::Result<Self, ::bento::DecodingError> {
+                #body
+            }
+        }
+    }
+}
+
+/// Decodes an enum stored either as the name of a unit variant or as a dictionary with a single
+/// key, the name of the variant, holding its content.
+fn decode_external(variants: &[Variant], deny_unknown_fields: bool) -> TokenStream {
+    let (unit_variants, variants): (Vec<_>, Vec<_>) = variants
+        .iter()
+        .partition(|variant| matches!(variant.fields, Fields::Unit));
+
+    let unknown_variant = quote! {
+        ::bento::DecodingError::unknown_variant(
+            ::std::string::String::from_utf8_lossy(variant).into_owned(),
+        )
+    };
+
+    let unit_arms = unit_variants.iter().map(|variant| {
+        let ident = variant.ident;
+        let names = name_literals(variant);
+
+        quote! {
+            #(#names)|* => ::std::result::Result::Ok(Self::#ident),
+        }
+    });
+
+    let arms = variants.iter().map(|variant| {
+        let names = name_literals(variant);
+        let value = decode_variant(variant, deny_unknown_fields);
+
+        quote! {
+            #(#names)|* => #value,
+        }
+    });
+
+    let decode_unit_variant = if unit_variants.is_empty() {
+        quote!(::std::result::Result::Err(#unknown_variant))
+    } else {
+        quote! {
+            match variant {
+                #(#unit_arms)*
+                _ => ::std::result::Result::Err(#unknown_variant),
+            }
+        }
+    };
+
+    let decode_variant = if variants.is_empty() {
+        quote!(return ::std::result::Result::Err(#unknown_variant))
+    } else {
+        quote! {
+            match variant {
+                #(#arms)*
+                _ => return ::std::result::Result::Err(#unknown_variant),
+            }
+        }
+    };
+
+    quote! {
+        match object {
+            ::bento::Object::ByteString(variant, _) => #decode_unit_variant,
+            object => {
+                let mut dictionary = object.try_dictionary()?;
+
+                let value = match dictionary.next_pair()? {
+                    ::std::option::Option::Some((variant, object)) => #decode_variant,
+                    ::std::option::Option::None => {
+                        return ::std::result::Result::Err(
+                            ::bento::DecodingError::unexpected_object(
+                                "Dictionary with a single key",
+                                "empty Dictionary",
+                            ),
+                        )
+                    }
+                };
+
+                if let ::std::option::Option::Some((key, _)) = dictionary.next_pair()? {
+                    return ::std::result::Result::Err(::bento::DecodingError::unexpected_field(
+                        ::std::string::String::from_utf8_lossy(key).into_owned(),
+                    ));
+                }
+
+                ::std::result::Result::Ok(value)
+            }
+        }
+    }
+}
+
+/// Decodes an enum stored as a dictionary holding the name of the variant under `tag`.
+///
+/// The tag can appear anywhere in the dictionary, so it is looked up ahead of decoding the
+/// fields of the variant.
+fn decode_internal(tag: &str, variants: &[Variant], deny_unknown_fields: bool) -> TokenStream {
+    let tag_literal = LitByteStr::new(tag.as_bytes(), proc_macro2::Span::call_site());
+
+    let arms = variants.iter().map(|variant| {
+        let ident = variant.ident;
+        let names = name_literals(variant);
+
+        let value = match &variant.fields {
+            Fields::Unit => quote! {
+                {
+                    object.skip()?;
+                    ::std::result::Result::Ok(Self::#ident)
+                }
+            },
+            fields => {
+                let value =
+                    decode_fields(fields, quote!(Self::#ident), Some(tag), deny_unknown_fields);
+                quote!(::std::result::Result::Ok(#value))
+            }
+        };
+
+        quote! {
+            #(#names)|* => #value,
+        }
+    });
+
+    quote! {
+        let variant = object.lookahead(|object| {
+            let mut dictionary = object.try_dictionary()?;
+
+            while let ::std::option::Option::Some((key, value)) = dictionary.next_pair()? {
+                if key == #tag_literal {
+                    return value.try_byte_string();
+                }
+
+                value.skip()?;
+            }
+
+            ::std::result::Result::Err(::bento::DecodingError::missing_field(#tag))
+        })?;
+
+        match variant {
+            #(#arms)*
+            _ => ::std::result::Result::Err(::bento::DecodingError::unknown_variant(
+                ::std::string::String::from_utf8_lossy(variant).into_owned(),
+            )),
+        }
+    }
+}
+
+/// Decodes an enum stored as just the content of one of its variants, trying each in turn.
+fn decode_untagged(name: &Ident, variants: &[Variant], deny_unknown_fields: bool) -> TokenStream {
+    let name = name.to_string();
+
+    let attempts = variants.iter().map(|variant| {
+        let value = match &variant.fields {
+            Fields::Unit => {
+                let ident = variant.ident;
+                let names = name_literals(variant);
+
+                quote! {
+                    match object.try_byte_string()? {
+                        #(#names)|* => Self::#ident,
+                        variant => {
+                            return ::std::result::Result::Err(
+                                ::bento::DecodingError::unknown_variant(
+                                    ::std::string::String::from_utf8_lossy(variant).into_owned(),
+                                ),
+                            )
+                        }
+                    }
+                }
+            }
+            _ => decode_variant(variant, deny_unknown_fields),
+        };
+
+        quote! {
+            if let ::std::result::Result::Ok(value) = object.lookahead(
+                |object| -> ::std::result::Result<Self, ::bento::DecodingError> {
+                    ::std::result::Result::Ok(#value)
+                },
+            ) {
+                object.skip()?;
+                return ::std::result::Result::Ok(value);
+            }
+        }
+    });
+
+    quote! {
+        #(#attempts)*
+
+        ::std::result::Result::Err(::bento::DecodingError::no_matching_variant(#name))
+    }
+}
+
+/// Decodes the content of a variant that is not a unit variant, a newtype variant holds its
+/// value directly.
+fn decode_variant(variant: &Variant, deny_unknown_fields: bool) -> TokenStream {
+    let ident = variant.ident;
+
+    match &variant.fields {
+        Fields::Unnamed(types) if types.len() == 1 => quote!(Self::#ident(object.decode()?)),
+        fields => decode_fields(fields, quote!(Self::#ident), None, deny_unknown_fields),
+    }
+}
+
+/// Returns an expression building `path` out of `object`, returning early on errors.
+///
+/// The `tag` of an internally tagged enum is never treated as an unknown field.
+fn decode_fields(
+    fields: &Fields,
+    path: TokenStream,
+    tag: Option<&str>,
+    deny_unknown_fields: bool,
+) -> TokenStream {
+    match fields {
+        Fields::Unit => path,
+        Fields::Named(fields) => decode_named(fields, path, tag, deny_unknown_fields),
+        Fields::Unnamed(types) => decode_unnamed(types, path),
+    }
+}
+
+/// Decodes named fields into local variables, used for the variants of enums.
+fn decode_named(
+    fields: &[Field],
+    path: TokenStream,
+    tag: Option<&str>,
+    deny_unknown_fields: bool,
+) -> TokenStream {
+    let place = |index: usize| {
+        let variable = format_ident!("__field{}", index);
+        quote!(#variable)
+    };
+
+    let declarations = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| !field.skip)
+        .map(|(index, field)| {
+            let variable = place(index);
+            let ty = field.ty;
+
+            quote! {
+                let mut #variable: ::std::option::Option<#ty> = ::std::option::Option::None;
+            }
+        });
+
+    let arms = field_arms(fields, place);
+    let initializers = field_initializers(fields, place);
+
+    let tag_arm = tag.map(|tag| {
+        let tag_literal = LitByteStr::new(tag.as_bytes(), Span::call_site());
+        quote!(#tag_literal => value.skip()?,)
+    });
+
+    let unknown_field = if deny_unknown_fields {
+        unexpected_field()
+    } else {
+        quote!(value.skip()?)
+    };
+
+    quote! {
+        {
+            let mut dictionary = object.try_dictionary()?;
+
+            #(#declarations)*
+
+            while let ::std::option::Option::Some((key, value)) = dictionary.next_pair()? {
+                match key {
+                    #(#arms,)*
+                    #tag_arm
+                    _ => #unknown_field,
+                }
+            }
+
+            #path {
+                #(#initializers,)*
+            }
+        }
+    }
+}
+
+/// Decodes a struct with named fields through [`FromBencodeFields`], which also allows it to be
+/// flattened into another struct.
+///
+/// The fields decoded so far are kept in a builder struct hidden in an anonymous constant.
+fn expand_named(container: &Container, fields: &[Field]) -> TokenStream {
+    let name = container.ident;
+
+    let mut generics = add_trait_bounds(container.generics.clone());
+    let where_clause = generics.make_where_clause();
+    for field in fields.iter().filter(|field| field.flatten) {
+        let ty = field.ty;
+        where_clause
+            .predicates
+            .push(parse_quote!(#ty: ::bento::FromBencodeFields<'__de>));
+    }
+    let (impl_generics, builder_generics, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = container.generics.split_for_impl();
+
+    let place = |index: usize| {
+        let variable = format_ident!("__field{}", index);
+        quote!(builder.#variable)
+    };
+
+    let builder_fields = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| !field.skip)
+        .map(|(index, field)| {
+            let variable = format_ident!("__field{}", index);
+            let ty = field.ty;
+
+            if field.flatten {
+                quote!(#variable: <#ty as ::bento::FromBencodeFields<'__de>>::Builder)
+            } else {
+                quote!(#variable: ::std::option::Option<#ty>)
+            }
+        });
+
+    let builder_defaults = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| !field.skip)
+        .map(|(index, field)| {
+            let variable = format_ident!("__field{}", index);
+
+            if field.flatten {
+                quote!(#variable: ::std::default::Default::default())
+            } else {
+                quote!(#variable: ::std::option::Option::None)
+            }
+        });
+
+    let arms = field_arms(fields, place);
+    let initializers = field_initializers(fields, place);
+
+    // Keys that are not ours are offered to every flattened field in turn
+    let flattened = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| field.flatten)
+        .map(|(index, field)| {
+            let builder = place(index);
+            let ty = field.ty;
+
+            quote! {
+                let value = match <#ty as ::bento::FromBencodeFields<'__de>>::decode_field(
+                    &mut #builder,
+                    key,
+                    value,
+                )? {
+                    ::std::option::Option::Some(value) => value,
+                    ::std::option::Option::None => {
+                        return ::std::result::Result::Ok(::std::option::Option::None)
+                    }
+                };
+            }
+        });
+
+    let decode_field = if arms.is_empty() {
+        quote! {
+            #(#flattened)*
+            ::std::result::Result::Ok(::std::option::Option::Some(value))
+        }
+    } else {
+        quote! {
+            match key {
+                #(#arms,)*
+                _ => {
+                    #(#flattened)*
+                    return ::std::result::Result::Ok(::std::option::Option::Some(value));
+                }
+            }
+
+            ::std::result::Result::Ok(::std::option::Option::None)
+        }
+    };
+
+    let unknown_field = if container.deny_unknown_fields {
+        let error = unexpected_field();
+        quote! {
+            if <Self as ::bento::FromBencodeFields<'__de>>::decode_field(&mut builder, key, value)?
+                .is_some()
+            {
+                #error;
+            }
+        }
+    } else {
+        quote! {
+            if let ::std::option::Option::Some(value) =
+                <Self as ::bento::FromBencodeFields<'__de>>::decode_field(&mut builder, key, value)?
+            {
+                value.skip()?;
+            }
+        }
+    };
+
+    quote! {
+        const _: () = {
+            pub struct __Builder #impl_generics #where_clause {
+                #(#builder_fields,)*
+                __marker: ::std::marker::PhantomData<fn(&'__de ()) -> #name #ty_generics>,
+            }
+
+            impl #impl_generics ::std::default::Default for __Builder #builder_generics
+            #where_clause
+            {
+                fn default() -> Self {
+                    Self {
+                        #(#builder_defaults,)*
+                        __marker: ::std::marker::PhantomData,
+                    }
+                }
+            }
+
+            impl #impl_generics ::bento::FromBencodeFields<'__de> for #name #ty_generics
+            #where_clause
+            {
+                type Builder = __Builder #builder_generics;
+
+                #[allow(unused_variables)]
+                fn decode_field<'__obj>(
+                    builder: &mut Self::Builder,
+                    key: &'__de [u8],
+                    value: ::bento::Object<'__obj, '__de>,
+                ) -> ::std::result::Result<
+                    ::std::option::Option<::bento::Object<'__obj, '__de>>,
+                    ::bento::DecodingError,
+                > {
+                    #decode_field
+                }
+
+                #[allow(unused_variables)]
+                fn finish(
+                    builder: Self::Builder,
+                ) -> ::std::result::Result<Self, ::bento::DecodingError> {
+                    ::std::result::Result::Ok(Self {
+                        #(#initializers,)*
+                    })
+                }
+            }
+
+            impl #impl_generics ::bento::FromBencode<'__de> for #name #ty_generics #where_clause {
+                fn decode(
+                    object: ::bento::Object<'_, '__de>,
+                ) -> ::std::result::Result<Self, ::bento::DecodingError> {
+                    let mut dictionary = object.try_dictionary()?;
+                    let mut builder: <Self as ::bento::FromBencodeFields<'__de>>::Builder =
+                        ::std::default::Default::default();
+
+                    while let ::std::option::Option::Some((key, value)) = dictionary.next_pair()? {
+                        #unknown_field
+                    }
+
+                    <Self as ::bento::FromBencodeFields<'__de>>::finish(builder)
+                }
+            }
+        };
+    }
+}
+
+/// Match arms storing the value of every field that is not skipped nor flattened into its place.
+fn field_arms(fields: &[Field], place: impl Fn(usize) -> TokenStream) -> Vec<TokenStream> {
+    fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| !field.skip && !field.flatten)
+        .map(|(index, field)| {
+            let place = place(index);
+            let key_literals = field
+                .keys()
+                .map(|key| LitByteStr::new(key.as_bytes(), field.ident.span()));
+
+            quote! {
+                #(#key_literals)|* => #place = ::std::option::Option::Some(value.decode()?)
+            }
+        })
+        .collect()
+}
+
+/// Field initializers taking every value out of its place, falling back to defaults.
+fn field_initializers(fields: &[Field], place: impl Fn(usize) -> TokenStream) -> Vec<TokenStream> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let ident = field.ident;
+            let key = &field.key;
+            let place = place(index);
+            let ty = field.ty;
+
+            if field.skip {
+                let default = match &field.default {
+                    DefaultValue::Path(path) => quote!(#path()),
+                    _ => quote!(::std::default::Default::default()),
+                };
+
+                return quote!(#ident: #default);
+            }
+
+            if field.flatten {
+                return quote!(#ident: <#ty as ::bento::FromBencodeFields<'__de>>::finish(#place)?);
+            }
+
+            match &field.default {
+                DefaultValue::Path(path) => quote! {
+                    #ident: #place.unwrap_or_else(#path)
+                },
+                DefaultValue::Default => quote! {
+                    #ident: #place.unwrap_or_default()
+                },
+                DefaultValue::None if field.optional => quote! {
+                    #ident: #place.unwrap_or(::std::option::Option::None)
+                },
+                DefaultValue::None => quote! {
+                    #ident: #place.ok_or(::bento::DecodingError::missing_field(#key))?
+                },
+            }
+        })
+        .collect()
+}
+
+fn unexpected_field() -> TokenStream {
+    quote! {
+        return ::std::result::Result::Err(::bento::DecodingError::unexpected_field(
+            ::std::string::String::from_utf8_lossy(key).into_owned(),
+        ))
+    }
+}
+
+fn decode_unnamed(types: &[&Type], path: TokenStream) -> TokenStream {
+    let length = types.len();
+    let variables: Vec<_> = (0..length)
+        .map(|index| format_ident!("__field{}", index))
+        .collect();
+
+    quote! {
+        {
+            let mut list = object.try_list()?;
+
+            #(
+                let #variables = match list.next_object()? {
+                    ::std::option::Option::Some(object) => object.decode()?,
+                    ::std::option::Option::None => {
+                        return ::std::result::Result::Err(
+                            ::bento::DecodingError::invalid_length(#length),
+                        )
+                    }
+                };
+            )*
+
+            if list.next_object()?.is_some() {
+                return ::std::result::Result::Err(::bento::DecodingError::invalid_length(#length));
+            }
+
+            #path(#(#variables),*)
+        }
+    }
+}
+
+fn name_literals<'a>(variant: &'a Variant) -> impl Iterator<Item = LitByteStr> + 'a {
+    variant
+        .names()
+        .map(|name| LitByteStr::new(name.as_bytes(), variant.ident.span()))
+}
+
+/// Adds the `'__de` lifetime of the input, which outlives every lifetime of the type so that
+/// fields can borrow from it, and requires every type parameter to be decodable itself.
+fn add_trait_bounds(mut generics: Generics) -> Generics {
+    let mut input_lifetime: LifetimeDef = parse_quote!('__de);
+
+    for param in &mut generics.params {
+        match param {
+            GenericParam::Type(type_param) => {
+                type_param
+                    .bounds
+                    .push(parse_quote!(::bento::FromBencode<'__de>));
+            }
+            GenericParam::Lifetime(lifetime) => {
+                input_lifetime.bounds.push(lifetime.lifetime.clone());
+            }
+            GenericParam::Const(_) => {}
+        }
+    }
+
+    generics
+        .params
+        .insert(0, GenericParam::Lifetime(input_lifetime));
+
+    generics
+}
@@ -1,45 +1,54 @@
+mod ast;
+mod attr;
+mod from_bencode;
+mod to_bencode;
+
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput};
+use syn::{parse_macro_input, DeriveInput};
+
+use ast::Container;
 
-#[proc_macro_derive(Bencode)]
+/// Derives both `FromBencode` and `ToBencode` from a single description of the type.
+#[proc_macro_derive(Bencode, attributes(bento))]
 pub fn bencode_derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let gen = quote! {};
 
-    gen.into()
+    match Container::from_ast(&input) {
+        Ok(container) => {
+            let from_bencode = from_bencode::expand(&container);
+            let to_bencode = to_bencode::expand(&container);
+
+            quote! {
+                #from_bencode
+                #to_bencode
+            }
+        }
+        Err(error) => error.to_compile_error(),
+    }
+    .into()
 }
 
-#[proc_macro_derive(FromBencode)]
+#[proc_macro_derive(FromBencode, attributes(bento))]
 pub fn from_bencode_derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
-    let name = &input.ident;
-
-    if let Data::Struct(data) = input.data {}
-
-    let gen = quote! {
-        impl FromBencode for #name {
-
-        }
-    };
-
-    gen.into()
+    Container::from_ast(&input)
+        .map_or_else(
+            |error| error.to_compile_error(),
+            |container| from_bencode::expand(&container),
+        )
+        .into()
 }
 
-#[proc_macro_derive(ToBencode)]
+#[proc_macro_derive(ToBencode, attributes(bento))]
 pub fn to_bencode_derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
-    let name = &input.ident;
-
-    if let Data::Struct(data) = input.data {}
-
-    let gen = quote! {
-        impl ToBencode for #name {
-
-        }
-    };
-
-    gen.into()
+    Container::from_ast(&input)
+        .map_or_else(
+            |error| error.to_compile_error(),
+            |container| to_bencode::expand(&container),
+        )
+        .into()
 }
@@ -0,0 +1,292 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse_quote, GenericParam, Generics, LitByteStr};
+
+use crate::ast::{sorted_fields, Container, Data, Field, Fields, Tagging, Variant};
+
+pub fn expand(container: &Container) -> TokenStream {
+    let name = container.ident;
+
+    let generics = add_trait_bounds(container.generics.clone());
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &container.data {
+        Data::Struct(Fields::Named(fields)) => return expand_named(container, fields),
+        Data::Struct(fields) => {
+            let pattern = bind_fields(fields, quote!(Self));
+            let content = encode_fields(fields, None);
+
+            quote! {
+                let #pattern = self;
+                #content
+            }
+        }
+        Data::Transparent(fields) => {
+            let pattern = bind_fields(fields, quote!(Self));
+
+            quote! {
+                let #pattern = self;
+                encoder.emit(__field0);
+            }
+        }
+        Data::Enum(tagging, variants) => {
+            let arms = variants.iter().map(|variant| {
+                let ident = variant.ident;
+                let pattern = bind_fields(&variant.fields, quote!(Self::#ident));
+                let content = encode_variant(tagging, variant);
+
+                quote! {
+                    #pattern => {
+                        #content
+                    }
+                }
+            });
+
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+    };
+
+    quote! {
+        impl #impl_generics ::bento::ToBencode for #name #ty_generics #where_clause {
+            fn encode(&self, encoder: &mut ::bento::Encoder) {
+                #body
+            }
+        }
+    }
+}
+
+/// Encodes a struct with named fields, also implementing [`ToBencodeFields`] so it can be
+/// flattened into another struct.
+///
+/// The order of the keys is fixed at expansion time, unless some fields are flattened in which
+/// case the pairs are sorted when encoding.
+fn expand_named(container: &Container, fields: &[Field]) -> TokenStream {
+    let name = container.ident;
+
+    let mut generics = add_trait_bounds(container.generics.clone());
+    let where_clause = generics.make_where_clause();
+    for field in fields.iter().filter(|field| field.flatten) {
+        let ty = field.ty;
+        where_clause
+            .predicates
+            .push(parse_quote!(#ty: ::bento::ToBencodeFields));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let pattern = bind_named(fields, quote!(Self));
+    let pairs = named_pairs(fields, None);
+
+    let flattened: Vec<_> = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| field.flatten)
+        .map(|(index, _)| {
+            let variable = format_ident!("__field{}", index);
+            quote!(::bento::ToBencodeFields::encode_fields(#variable, dictionary);)
+        })
+        .collect();
+
+    let encode = if flattened.is_empty() {
+        let content = encode_named(fields, None);
+
+        quote! {
+            let #pattern = self;
+            #content
+        }
+    } else {
+        quote! {
+            encoder.emit_sorting_dictionary(|dictionary| {
+                ::bento::ToBencodeFields::encode_fields(self, dictionary);
+            });
+        }
+    };
+
+    quote! {
+        impl #impl_generics ::bento::ToBencode for #name #ty_generics #where_clause {
+            fn encode(&self, encoder: &mut ::bento::Encoder) {
+                #encode
+            }
+        }
+
+        impl #impl_generics ::bento::ToBencodeFields for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn encode_fields(&self, dictionary: &mut ::bento::SortingDictionaryEncoder) {
+                let #pattern = self;
+                #(#pairs)*
+                #(#flattened)*
+            }
+        }
+    }
+}
+
+fn encode_variant(tagging: &Tagging, variant: &Variant) -> TokenStream {
+    let name = &variant.name;
+
+    match (tagging, &variant.fields) {
+        (Tagging::Internal(tag), fields) => {
+            let tag = Some((tag.as_str(), name.as_str()));
+
+            match fields {
+                Fields::Named(fields) => encode_named(fields, tag),
+                _ => encode_named(&[], tag),
+            }
+        }
+        (_, Fields::Unit) => quote!(encoder.emit_byte_string(#name);),
+        (Tagging::External, fields) => {
+            let name = LitByteStr::new(name.as_bytes(), variant.ident.span());
+            let content = encode_variant_content(fields);
+
+            quote! {
+                encoder.emit_dictionary(|mut dictionary| {
+                    dictionary.emit_pair_with(#name, |encoder| {
+                        #content
+                    });
+                });
+            }
+        }
+        (Tagging::Untagged, fields) => encode_variant_content(fields),
+    }
+}
+
+/// Encodes the content of a variant, a newtype variant holds its value directly.
+fn encode_variant_content(fields: &Fields) -> TokenStream {
+    match fields {
+        Fields::Unnamed(types) if types.len() == 1 => quote!(encoder.emit(__field0);),
+        fields => encode_fields(fields, None),
+    }
+}
+
+/// Encodes the fields bound by [`bind_fields`] into `encoder`.
+fn encode_fields(fields: &Fields, tag: Option<(&str, &str)>) -> TokenStream {
+    match fields {
+        Fields::Unit => encode_named(&[], tag),
+        Fields::Named(fields) => encode_named(fields, tag),
+        Fields::Unnamed(types) => {
+            if types.is_empty() {
+                return quote!(encoder.emit_list(|_| {}););
+            }
+
+            let variables = (0..types.len()).map(|index| format_ident!("__field{}", index));
+
+            quote! {
+                encoder.emit_list(|encoder| {
+                    #(encoder.emit(#variables);)*
+                });
+            }
+        }
+    }
+}
+
+/// Encodes named fields as a dictionary, along with the tag of an internally tagged enum.
+fn encode_named(fields: &[Field], tag: Option<(&str, &str)>) -> TokenStream {
+    let pairs = named_pairs(fields, tag);
+
+    if pairs.is_empty() {
+        return quote!(encoder.emit_dictionary(|_| {}););
+    }
+
+    quote! {
+        encoder.emit_dictionary(|mut dictionary| {
+            #(#pairs)*
+        });
+    }
+}
+
+/// Statements emitting the pair of every encoded field into `dictionary`, sorted by key.
+fn named_pairs(fields: &[Field], tag: Option<(&str, &str)>) -> Vec<TokenStream> {
+    let mut pairs: Vec<_> = sorted_fields(fields)
+        .into_iter()
+        .map(|field| {
+            // Bindings are numbered after the position of the field, skipped fields included
+            let index = fields
+                .iter()
+                .position(|other| std::ptr::eq(other, field))
+                .unwrap();
+            let variable = format_ident!("__field{}", index);
+            let key_literal = LitByteStr::new(field.key.as_bytes(), field.ident.span());
+
+            let pair = if field.optional {
+                quote! {
+                    if let ::std::option::Option::Some(value) = #variable {
+                        dictionary.emit_pair(#key_literal, value);
+                    }
+                }
+            } else {
+                quote! {
+                    dictionary.emit_pair(#key_literal, #variable);
+                }
+            };
+
+            let pair = match &field.skip_encoding_if {
+                Some(predicate) => quote! {
+                    if !#predicate(#variable) {
+                        #pair
+                    }
+                },
+                None => pair,
+            };
+
+            (field.key.as_bytes(), pair)
+        })
+        .collect();
+
+    if let Some((tag, name)) = tag {
+        let tag_literal = LitByteStr::new(tag.as_bytes(), Span::call_site());
+        let position = pairs.partition_point(|(key, _)| *key < tag.as_bytes());
+
+        pairs.insert(
+            position,
+            (
+                tag.as_bytes(),
+                quote! {
+                    dictionary.emit_pair(#tag_literal, #name);
+                },
+            ),
+        );
+    }
+
+    pairs.into_iter().map(|(_, pair)| pair).collect()
+}
+
+/// Returns a pattern binding a reference to every encoded field as `__field{index}`.
+fn bind_fields(fields: &Fields, path: TokenStream) -> TokenStream {
+    match fields {
+        Fields::Unit => path,
+        Fields::Named(fields) => bind_named(fields, path),
+        Fields::Unnamed(types) => {
+            let variables = (0..types.len()).map(|index| format_ident!("__field{}", index));
+
+            quote!(#path(#(#variables),*))
+        }
+    }
+}
+
+fn bind_named(fields: &[Field], path: TokenStream) -> TokenStream {
+    let bindings = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| !field.skip)
+        .map(|(index, field)| {
+            let ident = field.ident;
+            let variable = format_ident!("__field{}", index);
+
+            quote!(#ident: #variable)
+        });
+
+    quote!(#path { #(#bindings,)* .. })
+}
+
+/// Requires every type parameter to be encodable itself.
+fn add_trait_bounds(mut generics: Generics) -> Generics {
+    for param in &mut generics.params {
+        if let GenericParam::Type(type_param) = param {
+            type_param.bounds.push(parse_quote!(::bento::ToBencode));
+        }
+    }
+
+    generics
+}
@@ -0,0 +1,99 @@
+use bento::{DecodingError, DecodingErrorKind, FromBencode, ToBencode};
+use bento_derive::Bencode;
+
+#[derive(Debug, PartialEq, Bencode)]
+struct Info {
+    #[bento(rename = "piece length")]
+    piece_length: u64,
+    #[bento(alias = "title")]
+    name: String,
+    #[bento(default, skip_encoding_if = "Vec::is_empty")]
+    files: Vec<String>,
+    #[bento(default = "default_private")]
+    private: u8,
+    #[bento(skip)]
+    cached: Option<u64>,
+}
+
+fn default_private() -> u8 {
+    1
+}
+
+#[test]
+fn decode_renamed_field() -> anyhow::Result<()> {
+    let info = Info::from_bencode(b"d4:name4:test12:piece lengthi16384e7:privatei0ee")?;
+
+    assert_eq!(
+        Info {
+            piece_length: 16384,
+            name: "test".to_string(),
+            files: Vec::new(),
+            private: 0,
+            cached: None,
+        },
+        info
+    );
+
+    Ok(())
+}
+
+#[test]
+fn decode_alias() -> anyhow::Result<()> {
+    let info = Info::from_bencode(b"d12:piece lengthi16384e5:title4:teste")?;
+
+    assert_eq!("test", info.name);
+
+    Ok(())
+}
+
+#[test]
+fn decode_defaults() -> anyhow::Result<()> {
+    let info = Info::from_bencode(b"d4:name4:test12:piece lengthi16384ee")?;
+
+    assert!(info.files.is_empty());
+    assert_eq!(1, info.private);
+
+    Ok(())
+}
+
+#[test]
+fn decode_skipped_field_is_ignored() -> anyhow::Result<()> {
+    let info = Info::from_bencode(b"d6:cachedi1e4:name4:test12:piece lengthi16384ee")?;
+
+    assert_eq!(None, info.cached);
+
+    Ok(())
+}
+
+#[test]
+fn decode_missing_renamed_field() {
+    assert!(matches!(
+        Info::from_bencode(b"d4:name4:teste").map_err(DecodingError::into_kind),
+        Err(DecodingErrorKind::MissingField {
+            field: "piece length"
+        })
+    ));
+}
+
+#[test]
+fn encode_attributes() {
+    let mut info = Info {
+        piece_length: 16384,
+        name: "test".to_string(),
+        files: Vec::new(),
+        private: 1,
+        cached: Some(42),
+    };
+
+    assert_eq!(
+        b"d4:name4:test12:piece lengthi16384e7:privatei1ee".as_ref(),
+        info.to_bencode()
+    );
+
+    info.files.push("a".to_string());
+
+    assert_eq!(
+        b"d5:filesl1:ae4:name4:test12:piece lengthi16384e7:privatei1ee".as_ref(),
+        info.to_bencode()
+    );
+}
@@ -0,0 +1,36 @@
+use bento::{FromBencode, ToBencode};
+use bento_derive::Bencode;
+
+#[derive(Debug, PartialEq, Bencode)]
+struct Torrent<T> {
+    announce: String,
+    comment: Option<String>,
+    info: T,
+}
+
+#[derive(Debug, PartialEq, Bencode)]
+struct Info {
+    name: String,
+    length: u64,
+}
+
+#[test]
+fn round_trip() -> anyhow::Result<()> {
+    let bytes = b"d8:announce23:http://tracker/announce4:infod6:lengthi1024e4:name8:file.txtee";
+    let torrent = Torrent::<Info>::from_bencode(bytes)?;
+
+    assert_eq!(
+        Torrent {
+            announce: "http://tracker/announce".to_string(),
+            comment: None,
+            info: Info {
+                name: "file.txt".to_string(),
+                length: 1024,
+            },
+        },
+        torrent
+    );
+    assert_eq!(bytes.as_ref(), torrent.to_bencode());
+
+    Ok(())
+}
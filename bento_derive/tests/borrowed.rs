@@ -0,0 +1,117 @@
+use std::borrow::Cow;
+
+use bento::{DecodingError, DecodingErrorKind, FromBencode, ToBencode};
+use bento_derive::Bencode;
+
+#[derive(Debug, PartialEq, Bencode)]
+struct Info<'a> {
+    name: &'a str,
+    #[bento(rename = "piece length")]
+    piece_length: u64,
+    pieces: &'a [u8],
+}
+
+#[derive(Debug, PartialEq, bento_derive::FromBencode)]
+struct Torrent<'a> {
+    announce: Cow<'a, str>,
+    comment: Option<Cow<'a, [u8]>>,
+    info: Info<'a>,
+}
+
+#[derive(Debug, PartialEq, Bencode)]
+struct File<'a> {
+    path: Cow<'a, str>,
+    md5sum: Cow<'a, [u8]>,
+}
+
+#[derive(Debug, PartialEq, Bencode)]
+#[bento(transparent)]
+struct Pieces<'a>(&'a [u8]);
+
+#[derive(Debug, PartialEq, Bencode)]
+struct Chunks<'a> {
+    list: Vec<&'a [u8]>,
+}
+
+#[test]
+fn decode_borrowed_fields() -> anyhow::Result<()> {
+    let info = b"d4:name5:bento12:piece lengthi16384e6:pieces6:\x00\x01\x02\x03\x04\x05e";
+    let bytes = [
+        b"d8:announce16:udp://tracker:807:comment3:abc4:info".as_ref(),
+        info,
+        b"e",
+    ]
+    .concat();
+
+    let torrent = Torrent::from_bencode(&bytes)?;
+
+    assert!(matches!(
+        torrent.announce,
+        Cow::Borrowed("udp://tracker:80")
+    ));
+    assert!(matches!(torrent.comment, Some(Cow::Borrowed(b"abc"))));
+    assert_eq!(
+        Info {
+            name: "bento",
+            piece_length: 16384,
+            pieces: &[0, 1, 2, 3, 4, 5],
+        },
+        torrent.info
+    );
+    assert_eq!(info.as_ref(), torrent.info.to_bencode());
+
+    // The borrowed slices point into the input rather than into a copy
+    let pieces = torrent.info.pieces.as_ptr_range();
+    assert!(bytes.as_ptr_range().contains(&pieces.start));
+
+    Ok(())
+}
+
+#[test]
+fn round_trip_cow() -> anyhow::Result<()> {
+    let bytes = b"d6:md5sum4:\xde\xad\xbe\xef4:path7:a/b.txte";
+    let file = File::from_bencode(bytes)?;
+
+    assert!(matches!(file.path, Cow::Borrowed("a/b.txt")));
+    assert!(matches!(file.md5sum, Cow::Borrowed(b"\xde\xad\xbe\xef")));
+    assert_eq!(bytes.as_ref(), file.to_bencode());
+
+    let owned = File {
+        path: Cow::Owned("a/b.txt".to_string()),
+        md5sum: Cow::Owned(vec![0xde, 0xad, 0xbe, 0xef]),
+    };
+    assert_eq!(bytes.as_ref(), owned.to_bencode());
+
+    Ok(())
+}
+
+#[test]
+fn decode_borrowed_transparent() -> anyhow::Result<()> {
+    let pieces = Pieces::from_bencode(b"4:abcd")?;
+
+    assert_eq!(Pieces(b"abcd"), pieces);
+    assert_eq!(b"4:abcd".as_ref(), pieces.to_bencode());
+
+    Ok(())
+}
+
+#[test]
+fn round_trip_byte_slices() -> anyhow::Result<()> {
+    let bytes = b"d4:listl2:ab2:cdee";
+    let chunks = Chunks::from_bencode(bytes)?;
+
+    assert_eq!(vec![b"ab".as_ref(), b"cd".as_ref()], chunks.list);
+    assert_eq!(bytes.as_ref(), chunks.to_bencode());
+
+    Ok(())
+}
+
+#[test]
+fn decode_borrowed_invalid_utf8() {
+    let result = <&str>::from_bencode(b"2:\xff\xfe");
+
+    assert!(matches!(
+        result.map_err(DecodingError::into_kind),
+        Err(DecodingErrorKind::InvalidStr(_))
+    ));
+}
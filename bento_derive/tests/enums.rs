@@ -0,0 +1,166 @@
+use bento::{DecodingError, DecodingErrorKind, FromBencode, ToBencode};
+use bento_derive::Bencode;
+
+#[derive(Debug, PartialEq, Bencode)]
+enum Event {
+    Started,
+    #[bento(rename = "stopped")]
+    Stopped,
+    Progress(u64),
+    Range(u64, u64),
+    Peer {
+        ip: String,
+        port: u16,
+    },
+}
+
+#[derive(Debug, PartialEq, Bencode)]
+struct Arguments {
+    id: String,
+}
+
+#[derive(Debug, PartialEq, Bencode)]
+#[bento(tag = "y")]
+enum Message {
+    #[bento(rename = "q")]
+    Query { t: String, q: String, a: Arguments },
+    #[bento(rename = "r")]
+    Response { t: String, r: Arguments },
+    #[bento(rename = "p")]
+    Ping,
+}
+
+#[derive(Debug, PartialEq, Bencode)]
+struct Success {
+    interval: u32,
+}
+
+#[derive(Debug, PartialEq, Bencode)]
+struct Failure {
+    #[bento(rename = "failure reason")]
+    failure_reason: String,
+}
+
+#[derive(Debug, PartialEq, Bencode)]
+#[bento(untagged)]
+enum TrackerResponse {
+    Failure(Failure),
+    Success(Success),
+}
+
+#[test]
+fn externally_tagged() -> anyhow::Result<()> {
+    let cases: [(&[u8], Event); 5] = [
+        (b"7:Started", Event::Started),
+        (b"7:stopped", Event::Stopped),
+        (b"d8:Progressi50ee", Event::Progress(50)),
+        (b"d5:Rangeli1ei2eee", Event::Range(1, 2)),
+        (
+            b"d4:Peerd2:ip9:127.0.0.14:porti6881eee",
+            Event::Peer {
+                ip: "127.0.0.1".to_string(),
+                port: 6881,
+            },
+        ),
+    ];
+
+    for (bytes, event) in cases {
+        assert_eq!(event, Event::from_bencode(bytes)?);
+        assert_eq!(bytes, event.to_bencode());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn externally_tagged_unknown_variant() {
+    assert!(matches!(
+        Event::from_bencode(b"7:Unknown").map_err(DecodingError::into_kind),
+        Err(DecodingErrorKind::UnknownVariant { variant }) if variant == "Unknown"
+    ));
+    assert!(matches!(
+        Event::from_bencode(b"d8:Progressi50e5:Rangeli1ei2eee").map_err(DecodingError::into_kind),
+        Err(DecodingErrorKind::UnexpectedField { .. })
+    ));
+}
+
+#[test]
+fn internally_tagged() -> anyhow::Result<()> {
+    let query = b"d1:ad2:id20:abcdefghij0123456789e1:q4:ping1:t2:aa1:y1:qe";
+    let message = Message::from_bencode(query)?;
+
+    assert_eq!(
+        Message::Query {
+            t: "aa".to_string(),
+            q: "ping".to_string(),
+            a: Arguments {
+                id: "abcdefghij0123456789".to_string()
+            },
+        },
+        message
+    );
+    assert_eq!(query.as_ref(), message.to_bencode());
+
+    let response = b"d1:rd2:id20:mnopqrstuvwxyz123456e1:t2:aa1:y1:re";
+    let message = Message::from_bencode(response)?;
+
+    assert!(matches!(message, Message::Response { .. }));
+    assert_eq!(response.as_ref(), message.to_bencode());
+
+    assert_eq!(Message::Ping, Message::from_bencode(b"d1:y1:pe")?);
+    assert_eq!(b"d1:y1:pe".as_ref(), Message::Ping.to_bencode());
+
+    Ok(())
+}
+
+#[test]
+fn internally_tagged_missing_tag() {
+    assert!(matches!(
+        Message::from_bencode(b"d1:t2:aae").map_err(DecodingError::into_kind),
+        Err(DecodingErrorKind::MissingField { field: "y" })
+    ));
+}
+
+#[test]
+fn untagged() -> anyhow::Result<()> {
+    let success = b"d8:intervali1800ee";
+    let failure = b"d14:failure reason4:nopee";
+
+    assert_eq!(
+        TrackerResponse::Success(Success { interval: 1800 }),
+        TrackerResponse::from_bencode(success)?
+    );
+    assert_eq!(
+        TrackerResponse::Failure(Failure {
+            failure_reason: "nope".to_string()
+        }),
+        TrackerResponse::from_bencode(failure)?
+    );
+    assert_eq!(
+        success.as_ref(),
+        TrackerResponse::from_bencode(success)?.to_bencode()
+    );
+
+    assert!(matches!(
+        TrackerResponse::from_bencode(b"de").map_err(DecodingError::into_kind),
+        Err(DecodingErrorKind::NoMatchingVariant {
+            name: "TrackerResponse"
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn untagged_in_list() -> anyhow::Result<()> {
+    let responses =
+        Vec::<TrackerResponse>::from_bencode(b"ld14:failure reason4:nopeed8:intervali1800eee")?;
+
+    assert_eq!(2, responses.len());
+    assert_eq!(
+        TrackerResponse::Success(Success { interval: 1800 }),
+        responses[1]
+    );
+
+    Ok(())
+}
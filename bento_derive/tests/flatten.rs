@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+use bento::{DecodingError, DecodingErrorKind, FromBencode, ToBencode};
+use bento_derive::Bencode;
+use indexmap::IndexMap;
+
+#[derive(Debug, PartialEq, Bencode)]
+struct Info {
+    name: String,
+    length: u64,
+    #[bento(flatten)]
+    extra: HashMap<String, String>,
+}
+
+#[derive(Debug, PartialEq, Bencode)]
+struct Common {
+    #[bento(rename = "created by")]
+    created_by: String,
+    comment: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Bencode)]
+struct Torrent {
+    announce: String,
+    #[bento(flatten)]
+    common: Common,
+    #[bento(flatten)]
+    extra: HashMap<String, u64>,
+}
+
+#[derive(Debug, PartialEq, Bencode)]
+struct Raw {
+    name: String,
+    #[bento(flatten)]
+    extra: IndexMap<Vec<u8>, u64>,
+}
+
+#[derive(Debug, PartialEq, Bencode)]
+#[bento(deny_unknown_fields)]
+struct Strict {
+    a: u64,
+}
+
+#[test]
+fn unknown_keys_round_trip() -> anyhow::Result<()> {
+    let bytes = b"d6:lengthi1e1:m1:x4:name4:test1:z1:ye";
+    let info = Info::from_bencode(bytes)?;
+
+    assert_eq!("test", info.name);
+    assert_eq!(Some("x"), info.extra.get("m").map(String::as_str));
+    assert_eq!(Some("y"), info.extra.get("z").map(String::as_str));
+    assert_eq!(bytes.as_ref(), info.to_bencode());
+
+    Ok(())
+}
+
+#[test]
+fn byte_string_keys() -> anyhow::Result<()> {
+    let bytes = b"d1:ai1e1:bi2e4:name4:teste";
+    let raw = Raw::from_bencode(bytes)?;
+
+    assert_eq!(Some(&1), raw.extra.get(b"a".as_ref()));
+    assert_eq!(Some(&2), raw.extra.get(b"b".as_ref()));
+    assert_eq!(bytes.as_ref(), raw.to_bencode());
+
+    let map = IndexMap::<Vec<u8>, i64>::from_bencode(b"d1:ai1ee")?;
+    assert_eq!(Some(&1), map.get(b"a".as_ref()));
+
+    Ok(())
+}
+
+#[test]
+fn flatten_nested_struct() -> anyhow::Result<()> {
+    let bytes = b"d8:announce3:url7:comment2:hi10:created by5:bento13:creation datei1ee";
+    let torrent = Torrent::from_bencode(bytes)?;
+
+    assert_eq!(
+        Common {
+            created_by: "bento".to_string(),
+            comment: Some("hi".to_string()),
+        },
+        torrent.common
+    );
+    assert_eq!(Some(&1), torrent.extra.get("creation date"));
+    assert_eq!(bytes.as_ref(), torrent.to_bencode());
+
+    Ok(())
+}
+
+#[test]
+fn flatten_missing_nested_field() {
+    assert!(matches!(
+        Torrent::from_bencode(b"d8:announce3:urle").map_err(DecodingError::into_kind),
+        Err(DecodingErrorKind::MissingField {
+            field: "created by"
+        })
+    ));
+}
+
+#[test]
+fn deny_unknown_fields() -> anyhow::Result<()> {
+    assert_eq!(Strict { a: 1 }, Strict::from_bencode(b"d1:ai1ee")?);
+    assert!(matches!(
+        Strict::from_bencode(b"d1:ai1e1:bi2ee").map_err(DecodingError::into_kind),
+        Err(DecodingErrorKind::UnexpectedField { field }) if field == "b"
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use bento::{DecodingError, DecodingErrorKind, FromBencode, PathSegment};
+
+#[derive(Debug, PartialEq, bento_derive::FromBencode)]
+struct Peer {
+    ip: String,
+    port: u16,
+    id: Option<String>,
+}
+
+#[derive(Debug, PartialEq, bento_derive::FromBencode)]
+struct Response {
+    interval: u32,
+    peers: Vec<Peer>,
+}
+
+#[test]
+fn decode_struct() -> anyhow::Result<()> {
+    let peer = Peer::from_bencode(b"d2:id3:abc2:ip9:127.0.0.14:porti6881ee")?;
+
+    assert_eq!(
+        Peer {
+            ip: "127.0.0.1".to_string(),
+            port: 6881,
+            id: Some("abc".to_string()),
+        },
+        peer
+    );
+
+    Ok(())
+}
+
+#[test]
+fn decode_nested_struct() -> anyhow::Result<()> {
+    let response =
+        Response::from_bencode(b"d8:intervali1800e5:peersld2:ip9:127.0.0.14:porti6881eeee")?;
+
+    assert_eq!(
+        Response {
+            interval: 1800,
+            peers: vec![Peer {
+                ip: "127.0.0.1".to_string(),
+                port: 6881,
+                id: None,
+            }],
+        },
+        response
+    );
+
+    Ok(())
+}
+
+#[test]
+fn decode_skips_unknown_keys() -> anyhow::Result<()> {
+    let peer = Peer::from_bencode(b"d2:ip9:127.0.0.14:porti6881e5:extrald1:ai1eeee")?;
+
+    assert_eq!(6881, peer.port);
+
+    Ok(())
+}
+
+#[test]
+fn decode_missing_field() {
+    assert!(matches!(
+        Peer::from_bencode(b"d2:ip9:127.0.0.1e").map_err(DecodingError::into_kind),
+        Err(DecodingErrorKind::MissingField { field: "port" })
+    ));
+}
+
+#[test]
+fn decode_integer_overflow() {
+    assert!(matches!(
+        Peer::from_bencode(b"d2:ip9:127.0.0.14:porti65536ee").map_err(DecodingError::into_kind),
+        Err(DecodingErrorKind::IntegerOverflow { target: "u16" })
+    ));
+}
+
+#[test]
+fn decode_error_location() {
+    let bytes =
+        b"d8:intervali1800e5:peersld2:ip9:127.0.0.14:porti6881eed2:ip9:127.0.0.24:porti65536eeee";
+    let error = Response::from_bencode(bytes).unwrap_err();
+
+    assert!(matches!(
+        error.kind(),
+        DecodingErrorKind::IntegerOverflow { target: "u16" }
+    ));
+    assert_eq!(Some(76), error.offset());
+    assert_eq!(
+        [
+            PathSegment::Key("peers".to_string()),
+            PathSegment::Index(1),
+            PathSegment::Key("port".to_string()),
+        ],
+        error.path()
+    );
+    assert_eq!(
+        "Integer does not fit into u16 at peers[1].port (offset 76)",
+        error.to_string()
+    );
+}
+
+#[test]
+fn decode_missing_field_location() {
+    let error =
+        Response::from_bencode(b"d8:intervali1800e5:peersld2:ip9:127.0.0.1eee").unwrap_err();
+
+    assert!(matches!(
+        error.kind(),
+        DecodingErrorKind::MissingField { field: "port" }
+    ));
+    assert_eq!(
+        [PathSegment::Key("peers".to_string()), PathSegment::Index(0)],
+        error.path()
+    );
+}
+
+#[test]
+fn decode_strict() -> anyhow::Result<()> {
+    let canonical = b"d2:id3:abc2:ip9:127.0.0.14:porti6881ee";
+    let unsorted = b"d2:ip9:127.0.0.12:id3:abc4:porti6881ee";
+
+    assert_eq!(
+        Peer::from_bencode(canonical)?,
+        Peer::from_bencode_strict(canonical)?
+    );
+    assert_eq!(
+        Peer::from_bencode(canonical)?,
+        Peer::from_bencode(unsorted)?
+    );
+    assert!(matches!(
+        Peer::from_bencode_strict(unsorted).map_err(DecodingError::into_kind),
+        Err(DecodingErrorKind::UnsortedKey { key }) if key == "id"
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use std::borrow::Cow;
+
+use bento::{FromBencode, RawBencode, ToBencode};
+use bento_derive::Bencode;
+
+#[derive(Debug, PartialEq, Bencode)]
+struct Torrent<'a> {
+    announce: String,
+    info: RawBencode<'a>,
+}
+
+#[derive(Debug, PartialEq, Bencode)]
+struct Message<'a> {
+    id: RawBencode<'a>,
+    value: RawBencode<'a>,
+}
+
+#[test]
+fn capture_dictionary_verbatim() -> anyhow::Result<()> {
+    // The keys of the info dictionary are not sorted, it must be kept as is to be hashed
+    let info = b"d6:lengthi1024e4:name5:bento12:piece lengthi16384e4:listli1ei2eee";
+    let bytes = [b"d8:announce3:url4:info".as_ref(), info, b"e"].concat();
+
+    let torrent = Torrent::from_bencode(&bytes)?;
+
+    assert_eq!(info.as_ref(), torrent.info.as_bytes());
+    assert_eq!(bytes, torrent.to_bencode());
+
+    Ok(())
+}
+
+#[test]
+fn capture_scalars() -> anyhow::Result<()> {
+    let bytes = b"d2:idi-0e5:value5:bentoe";
+    let message = Message::from_bencode(bytes)?;
+
+    assert_eq!(b"i-0e", message.id.as_bytes());
+    assert_eq!(b"5:bento", message.value.as_bytes());
+    assert_eq!(bytes.as_ref(), message.to_bencode());
+
+    // Scalars are borrowed as written, leading zeros included
+    let raw = RawBencode::from_bencode(b"03:abc")?;
+    assert!(matches!(raw.0, Cow::Borrowed(b"03:abc")));
+
+    Ok(())
+}
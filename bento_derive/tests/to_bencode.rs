@@ -0,0 +1,52 @@
+use bento::ToBencode;
+
+#[derive(bento_derive::ToBencode)]
+struct Peer {
+    port: u16,
+    ip: String,
+    id: Option<String>,
+}
+
+#[derive(bento_derive::ToBencode)]
+struct Announce {
+    peers: Vec<Peer>,
+    peer_id: String,
+}
+
+#[test]
+fn encode_struct() {
+    let peer = Peer {
+        port: 6881,
+        ip: "127.0.0.1".to_string(),
+        id: Some("abc".to_string()),
+    };
+
+    assert_eq!(
+        b"d2:id3:abc2:ip9:127.0.0.14:porti6881ee".as_ref(),
+        peer.to_bencode()
+    );
+}
+
+#[test]
+fn encode_omits_none() {
+    let peer = Peer {
+        port: 6881,
+        ip: "127.0.0.1".to_string(),
+        id: None,
+    };
+
+    assert_eq!(b"d2:ip9:127.0.0.14:porti6881ee".as_ref(), peer.to_bencode());
+}
+
+#[test]
+fn encode_sorts_keys_by_raw_bytes() {
+    let announce = Announce {
+        peers: Vec::new(),
+        peer_id: "-BT0001-".to_string(),
+    };
+
+    assert_eq!(
+        b"d7:peer_id8:-BT0001-5:peerslee".as_ref(),
+        announce.to_bencode()
+    );
+}
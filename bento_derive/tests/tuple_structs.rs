@@ -0,0 +1,77 @@
+use bento::{DecodingError, DecodingErrorKind, FromBencode, ToBencode};
+use bento_derive::Bencode;
+
+#[derive(Debug, PartialEq, Bencode)]
+#[bento(transparent)]
+struct Port(u16);
+
+#[derive(Debug, PartialEq, Bencode)]
+#[bento(transparent)]
+struct Name {
+    inner: String,
+}
+
+#[derive(Debug, PartialEq, Bencode)]
+struct Node(String, Port);
+
+#[derive(Debug, PartialEq, Bencode)]
+struct Wrapper(u32);
+
+#[derive(Debug, PartialEq, Bencode)]
+struct Response {
+    nodes: Vec<Node>,
+}
+
+#[test]
+fn transparent() -> anyhow::Result<()> {
+    assert_eq!(Port(6881), Port::from_bencode(b"i6881e")?);
+    assert_eq!(b"i6881e".as_ref(), Port(6881).to_bencode());
+
+    let name = Name {
+        inner: "bento".to_string(),
+    };
+
+    assert_eq!(name, Name::from_bencode(b"5:bento")?);
+    assert_eq!(b"5:bento".as_ref(), name.to_bencode());
+
+    Ok(())
+}
+
+#[test]
+fn tuple_struct_as_list() -> anyhow::Result<()> {
+    let bytes = b"d5:nodesll9:127.0.0.1i6881eel9:127.0.0.2i6882eeee";
+    let response = Response::from_bencode(bytes)?;
+
+    assert_eq!(
+        Response {
+            nodes: vec![
+                Node("127.0.0.1".to_string(), Port(6881)),
+                Node("127.0.0.2".to_string(), Port(6882)),
+            ],
+        },
+        response
+    );
+    assert_eq!(bytes.as_ref(), response.to_bencode());
+
+    Ok(())
+}
+
+#[test]
+fn newtype_without_transparent_is_a_list() -> anyhow::Result<()> {
+    assert_eq!(Wrapper(1), Wrapper::from_bencode(b"li1ee")?);
+    assert_eq!(b"li1ee".as_ref(), Wrapper(1).to_bencode());
+
+    Ok(())
+}
+
+#[test]
+fn tuple_struct_invalid_length() {
+    assert!(matches!(
+        Node::from_bencode(b"l9:127.0.0.1e").map_err(DecodingError::into_kind),
+        Err(DecodingErrorKind::InvalidLength { expected: 2 })
+    ));
+    assert!(matches!(
+        Node::from_bencode(b"l9:127.0.0.1i1ei2ee").map_err(DecodingError::into_kind),
+        Err(DecodingErrorKind::InvalidLength { expected: 2 })
+    ));
+}
@@ -0,0 +1,60 @@
+use bento::{
+    Decoder, DecodingError, DecodingErrorKind, DecodingMode, FromBencode, ToBencode, Value,
+};
+use bento_derive::Bencode;
+use indexmap::IndexMap;
+
+#[derive(Debug, Bencode)]
+struct Torrent<'a> {
+    announce: String,
+    info: Value<'a>,
+    #[bento(flatten)]
+    extra: IndexMap<Vec<u8>, Value<'a>>,
+}
+
+#[test]
+fn value_field_and_catch_all() -> anyhow::Result<()> {
+    let bytes = b"d8:announce3:url7:comment2:hi4:infod6:lengthi1e4:name1:ae4:listli1eli2eeee";
+    let torrent = Torrent::from_bencode(bytes)?;
+
+    assert!(matches!(&torrent.info, Value::Dictionary(info) if info.len() == 2));
+    assert!(matches!(
+        torrent.extra.get(b"comment".as_ref()),
+        Some(Value::ByteString(comment)) if comment.as_ref() == b"hi"
+    ));
+    assert!(
+        matches!(torrent.extra.get(b"list".as_ref()), Some(Value::List(list)) if list.len() == 2)
+    );
+    assert_eq!(bytes.as_ref(), torrent.to_bencode());
+
+    Ok(())
+}
+
+#[test]
+fn list_of_values() -> anyhow::Result<()> {
+    let values = Vec::<Value>::from_bencode(b"li1e3:abcd1:ai2eee")?;
+
+    assert_eq!(3, values.len());
+    assert_eq!(b"li1e3:abcd1:ai2eee".as_ref(), values.to_bencode());
+
+    Ok(())
+}
+
+#[test]
+fn encode_sorts_keys() -> anyhow::Result<()> {
+    let value = Value::from_bencode(b"d1:bi1e1:ad1:di1e1:ci2eee")?;
+
+    assert_eq!(b"d1:ad1:ci2e1:di1ee1:bi1ee".as_ref(), value.to_bencode());
+
+    Ok(())
+}
+
+#[test]
+fn value_follows_decoder_mode() {
+    let mut decoder = Decoder::new(b"ld1:bi1e1:ai2eee").with_mode(DecodingMode::Strict);
+
+    assert!(matches!(
+        Value::from_decoder(&mut decoder).map_err(DecodingError::into_kind),
+        Err(DecodingErrorKind::UnsortedKey { key }) if key == "a"
+    ));
+}